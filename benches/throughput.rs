@@ -0,0 +1,67 @@
+//! Throughput of the translation on a generated model of a few megabytes.
+//!
+//! Run with `cargo bench --bench throughput`.
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use fzn2lp::{translate, translate_str, Options};
+use std::{fmt::Write, io};
+
+/// A model with many variables, linear constraints and set constraints.
+fn model(n: usize) -> String {
+    let mut fzn = String::new();
+    writeln!(fzn, "array [1..3] of int: coeffs = [1,-2,3];").unwrap();
+    for i in 0..n {
+        writeln!(fzn, "var 0..{}: x{i} :: output_var;", i % 100 + 1).unwrap();
+        writeln!(fzn, "var {{1,3,5,7,{}}}: y{i};", i + 9).unwrap();
+    }
+    writeln!(
+        fzn,
+        "array [1..{n}] of var int: xs :: output_array([1..{n}]) = [{}];",
+        (0..n)
+            .map(|i| format!("x{i}"))
+            .collect::<Vec<_>>()
+            .join(",")
+    )
+    .unwrap();
+    for i in 1..n {
+        writeln!(
+            fzn,
+            "constraint int_lin_le(coeffs,[x{i},y{i},x{}],{i});",
+            i - 1
+        )
+        .unwrap();
+        writeln!(fzn, "constraint set_in(y{i},{{1,3,5,7,{}}});", i + 9).unwrap();
+    }
+    writeln!(fzn, "solve satisfy;").unwrap();
+    fzn
+}
+
+fn throughput(c: &mut Criterion) {
+    let fzn = model(20_000);
+    let mut group = c.benchmark_group("throughput");
+    group.throughput(Throughput::Bytes(fzn.len() as u64));
+    group.sample_size(10);
+    group.bench_function("translate", |b| {
+        b.iter(|| translate(fzn.as_bytes(), io::sink(), Options::default()).unwrap())
+    });
+    group.bench_function("translate_str", |b| {
+        b.iter(|| translate_str(&fzn, io::sink(), Options::default()).unwrap())
+    });
+    group.bench_function("translate_constants", |b| {
+        let options = Options {
+            constants: true,
+            ..Options::default()
+        };
+        b.iter(|| translate(fzn.as_bytes(), io::sink(), options.clone()).unwrap())
+    });
+    group.bench_function("translate_parallel", |b| {
+        let options = Options {
+            parallel: true,
+            ..Options::default()
+        };
+        b.iter(|| translate(fzn.as_bytes(), io::sink(), options.clone()).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, throughput);
+criterion_main!(benches);
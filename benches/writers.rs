@@ -0,0 +1,117 @@
+//! Throughput of `write_fz_stmt` on synthetic models stressing single writers, and of the binary.
+//!
+//! Run with `cargo bench --bench writers`.
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use fzn2lp::{Options, Translator};
+use std::{fmt::Write, io, process::Command};
+
+/// Parameter and variable arrays with many elements.
+fn large_arrays(n: usize) -> Vec<String> {
+    let list = |f: &dyn Fn(usize) -> String| (0..n).map(f).collect::<Vec<_>>().join(",");
+    let mut stmts: Vec<_> = (0..n).map(|i| format!("var 0..{i}: x{i};")).collect();
+    stmts.push(format!(
+        "array [1..{n}] of int: a = [{}];",
+        list(&|i| (i * 7 % 1000).to_string())
+    ));
+    stmts.push(format!(
+        "array [1..{n}] of var int: xs :: output_array([1..{n}]) = [{}];",
+        list(&|i| format!("x{i}"))
+    ));
+    stmts.push(format!(
+        "constraint int_lin_le(a,[{}],{n});",
+        list(&|i| format!("x{i}"))
+    ));
+    stmts
+}
+
+/// Many constraints with few arguments each.
+fn small_constraints(n: usize) -> Vec<String> {
+    let mut stmts = vec!["var 0..9: x;".to_string(), "var 0..9: y;".to_string()];
+    stmts.extend((0..n).map(|i| match i % 3 {
+        0 => format!("constraint int_le(x,{i});"),
+        1 => "constraint int_ne(x,y) :: domain;".to_string(),
+        _ => format!("constraint int_plus(x,y,{i});"),
+    }));
+    stmts
+}
+
+/// Set parameters and domains with many elements.
+fn set_literals(n: usize, size: usize) -> Vec<String> {
+    (0..n)
+        .flat_map(|i| {
+            let mut set = String::new();
+            for e in 0..size {
+                write!(set, "{}{}", if e == 0 { "" } else { "," }, i + 3 * e).unwrap();
+            }
+            [
+                format!("set of int: s{i} = {{{set}}};"),
+                format!("var {{{set}}}: x{i};"),
+                format!("constraint set_in(x{i},{{{set}}});"),
+            ]
+        })
+        .collect()
+}
+
+/// Predicate declarations with parameters of every kind of type.
+fn predicates(n: usize) -> Vec<String> {
+    (0..n)
+        .map(|i| {
+            format!(
+                "predicate p{i}(var int: a, var 1..{i}: b, array [int] of var bool: c, \
+                 {{1,3,{i}}}: d, var set of 1..{i}: e, float: f, array [1..2] of var float: g);"
+            )
+        })
+        .collect()
+}
+
+/// Translates the statements one by one with [`Translator::write_fz_stmt`].
+fn write_all(stmts: &[String]) {
+    let mut translator = Translator::new(Options::default());
+    for stmt in stmts {
+        translator.write_fz_stmt(io::sink(), stmt).unwrap();
+    }
+}
+
+fn writers(c: &mut Criterion) {
+    let models = [
+        ("large_arrays", large_arrays(100_000)),
+        ("small_constraints", small_constraints(100_000)),
+        ("set_literals", set_literals(2_000, 100)),
+        ("predicates", predicates(20_000)),
+    ];
+    let mut group = c.benchmark_group("write_fz_stmt");
+    group.sample_size(10);
+    for (name, stmts) in &models {
+        let bytes = stmts.iter().map(|stmt| stmt.len() + 1).sum::<usize>();
+        group.throughput(Throughput::Bytes(bytes as u64));
+        group.bench_function(*name, |b| b.iter(|| write_all(stmts)));
+    }
+    group.finish();
+}
+
+/// Runs the release binary on a file, including process start, reading and writing.
+fn binary(c: &mut Criterion) {
+    let mut stmts = small_constraints(100_000);
+    stmts.extend(set_literals(500, 100));
+    stmts.push("solve satisfy;".to_string());
+    let fzn = stmts.join("\n");
+    let path = std::env::temp_dir().join(format!("fzn2lp-bench-{}.fzn", std::process::id()));
+    std::fs::write(&path, &fzn).unwrap();
+    let mut group = c.benchmark_group("binary");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(fzn.len() as u64));
+    group.bench_function("end_to_end", |b| {
+        b.iter(|| {
+            let output = Command::new(env!("CARGO_BIN_EXE_fzn2lp"))
+                .arg(&path)
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+        })
+    });
+    group.finish();
+    std::fs::remove_file(path).unwrap();
+}
+
+criterion_group!(benches, writers, binary);
+criterion_main!(benches);
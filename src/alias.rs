@@ -0,0 +1,405 @@
+//! Elimination of variables that are aliases of other variables.
+//!
+//! Integer and Boolean variables declared equal to another variable, or constrained equal by
+//! `int_eq` or `bool_eq`, are replaced by the first declared variable of their class, whose
+//! domain becomes the intersection of the domains of the class.
+use crate::{
+    par_decl_id,
+    presolve::{declared_domain, is_empty, tightened},
+    var_decl_id,
+};
+use flatzinc::{
+    ArrayOfBoolExpr, ArrayOfFloatExpr, ArrayOfIntExpr, ArrayOfSetExpr, BoolExpr, Expr, FloatExpr,
+    Goal, IntExpr, ParDeclItem, SetExpr, SetLiteralExpr, Stmt, VarDeclItem,
+};
+use std::collections::{HashMap, HashSet};
+
+/// An eliminated variable and the variable replacing it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Alias {
+    pub id: String,
+    pub representative: String,
+    /// The eliminated variable was annotated with `output_var`
+    pub output: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Int,
+    Bool,
+}
+
+/// Replaces aliased variables by the representatives of their classes.
+///
+/// Returns the eliminated variables in the order of their declarations. Classes whose
+/// merged domain is empty are left unchanged.
+pub fn eliminate(stmts: &mut Vec<(usize, Stmt)>) -> Vec<Alias> {
+    let mut vars: HashMap<String, (usize, Kind)> = HashMap::new();
+    let mut names = vec![];
+    for (_, stmt) in stmts.iter() {
+        let Stmt::Variable(d) = stmt else { continue };
+        let kind = match d {
+            VarDeclItem::Int { .. }
+            | VarDeclItem::IntInRange { .. }
+            | VarDeclItem::IntInSet { .. } => Kind::Int,
+            VarDeclItem::Bool { .. } => Kind::Bool,
+            _ => continue,
+        };
+        vars.insert(var_decl_id(d).to_string(), (names.len(), kind));
+        names.push(var_decl_id(d).to_string());
+    }
+    let mut parent: Vec<usize> = (0..names.len()).collect();
+    let mut equalities = HashSet::new();
+    let alias_of = |x: &str, y: &str| match (vars.get(x), vars.get(y)) {
+        (Some((i, k)), Some((j, l))) if k == l => Some((*i, *j)),
+        _ => None,
+    };
+    for (pos, (_, stmt)) in stmts.iter().enumerate() {
+        let pair = match stmt {
+            Stmt::Variable(
+                VarDeclItem::Int {
+                    id,
+                    expr: Some(IntExpr::VarParIdentifier(y)),
+                    ..
+                }
+                | VarDeclItem::IntInRange {
+                    id,
+                    expr: Some(IntExpr::VarParIdentifier(y)),
+                    ..
+                }
+                | VarDeclItem::IntInSet {
+                    id,
+                    expr: Some(IntExpr::VarParIdentifier(y)),
+                    ..
+                }
+                | VarDeclItem::Bool {
+                    id,
+                    expr: Some(BoolExpr::VarParIdentifier(y)),
+                    ..
+                },
+            ) => alias_of(id, y),
+            Stmt::Constraint(c) if c.id == "int_eq" || c.id == "bool_eq" => {
+                match c.exprs.as_slice() {
+                    [Expr::VarParIdentifier(x), Expr::VarParIdentifier(y)] => {
+                        let pair = alias_of(x, y);
+                        if pair.is_some() {
+                            equalities.insert(pos);
+                        }
+                        pair
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        if let Some((i, j)) = pair {
+            let (i, j) = (find(&mut parent, i), find(&mut parent, j));
+            // the earlier declaration represents the class
+            parent[i.max(j)] = i.min(j);
+        }
+    }
+
+    // merge the domains of each class into its representative
+    // values assigned from parameters become part of the domains
+    let mut params = HashMap::new();
+    let mut decls: HashMap<usize, VarDeclItem> = HashMap::new();
+    for (_, stmt) in stmts.iter() {
+        match stmt {
+            Stmt::Parameter(p @ (ParDeclItem::Int { .. } | ParDeclItem::Bool { .. })) => {
+                params.insert(par_decl_id(p).to_string(), p.clone());
+            }
+            Stmt::Variable(d) => {
+                if let Some((i, _)) = vars.get(var_decl_id(d)) {
+                    decls.insert(*i, d.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    let mut classes: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..names.len() {
+        let root = find(&mut parent, i);
+        if root != i {
+            classes.entry(root).or_default().push(i);
+        }
+    }
+    let mut representatives = HashMap::new();
+    let mut eliminated = HashMap::new();
+    for (root, members) in &classes {
+        let Some((_, mut domain)) = declared_domain(&decls[root], &params) else {
+            continue;
+        };
+        for i in members {
+            if let Some((_, other)) = declared_domain(&decls[i], &params) {
+                domain.merge(other);
+            }
+        }
+        if is_empty(&decls[root], &domain) {
+            continue;
+        }
+        let decl = tightened(&decls[root], &domain).map(|(decl, _)| decl);
+        representatives.insert(names[*root].clone(), decl);
+        for i in members {
+            eliminated.insert(names[*i].clone(), names[*root].clone());
+        }
+    }
+
+    let mut aliases = vec![];
+    let mut pos = 0;
+    stmts.retain_mut(|(_, stmt)| {
+        pos += 1;
+        match stmt {
+            Stmt::Variable(d) => {
+                let id = var_decl_id(d);
+                if let Some(representative) = eliminated.get(id) {
+                    aliases.push(Alias {
+                        id: id.to_string(),
+                        representative: representative.clone(),
+                        output: var_decl_annos(d).iter().any(|a| a.id == "output_var"),
+                    });
+                    return false;
+                }
+                if let Some(Some(decl)) = representatives.get(id) {
+                    *d = decl.clone();
+                }
+                rename_var_decl(d, &eliminated);
+            }
+            Stmt::Constraint(c) => {
+                if equalities.contains(&(pos - 1)) {
+                    if let [Expr::VarParIdentifier(x), _] = c.exprs.as_slice() {
+                        if eliminated.contains_key(x) || representatives.contains_key(x) {
+                            return false;
+                        }
+                    }
+                }
+                for e in &mut c.exprs {
+                    rename_expr(e, &eliminated);
+                }
+            }
+            Stmt::SolveItem(s) => rename_goal(&mut s.goal, &eliminated),
+            _ => {}
+        }
+        true
+    });
+    aliases
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    let mut i = i;
+    while parent[i] != root {
+        let next = parent[i];
+        parent[i] = root;
+        i = next;
+    }
+    root
+}
+
+fn var_decl_annos(d: &VarDeclItem) -> &[flatzinc::Annotation] {
+    match d {
+        VarDeclItem::Bool { annos, .. }
+        | VarDeclItem::Int { annos, .. }
+        | VarDeclItem::IntInRange { annos, .. }
+        | VarDeclItem::IntInSet { annos, .. } => annos,
+        _ => &[],
+    }
+}
+
+fn rename(id: &mut String, eliminated: &HashMap<String, String>) {
+    if let Some(representative) = eliminated.get(id) {
+        *id = representative.clone();
+    }
+}
+fn rename_expr(e: &mut Expr, eliminated: &HashMap<String, String>) {
+    match e {
+        Expr::VarParIdentifier(id) => rename(id, eliminated),
+        Expr::Set(s) => rename_set_literal(s, eliminated),
+        Expr::ArrayOfBool(v) => v.iter_mut().for_each(|e| rename_bool(e, eliminated)),
+        Expr::ArrayOfInt(v) => v.iter_mut().for_each(|e| rename_int(e, eliminated)),
+        Expr::ArrayOfFloat(v) => v.iter_mut().for_each(|e| rename_float(e, eliminated)),
+        Expr::ArrayOfSet(v) => v.iter_mut().for_each(|e| rename_set(e, eliminated)),
+        Expr::Bool(_) | Expr::Int(_) | Expr::Float(_) => {}
+    }
+}
+fn rename_bool(e: &mut BoolExpr, eliminated: &HashMap<String, String>) {
+    if let BoolExpr::VarParIdentifier(id) = e {
+        rename(id, eliminated);
+    }
+}
+fn rename_int(e: &mut IntExpr, eliminated: &HashMap<String, String>) {
+    if let IntExpr::VarParIdentifier(id) = e {
+        rename(id, eliminated);
+    }
+}
+fn rename_float(e: &mut FloatExpr, eliminated: &HashMap<String, String>) {
+    if let FloatExpr::VarParIdentifier(id) = e {
+        rename(id, eliminated);
+    }
+}
+fn rename_set(e: &mut SetExpr, eliminated: &HashMap<String, String>) {
+    match e {
+        SetExpr::Set(s) => rename_set_literal(s, eliminated),
+        SetExpr::VarParIdentifier(id) => rename(id, eliminated),
+    }
+}
+fn rename_set_literal(s: &mut SetLiteralExpr, eliminated: &HashMap<String, String>) {
+    match s {
+        SetLiteralExpr::IntInRange(lb, ub) => {
+            rename_int(lb, eliminated);
+            rename_int(ub, eliminated);
+        }
+        SetLiteralExpr::BoundedFloat(lb, ub) => {
+            rename_float(lb, eliminated);
+            rename_float(ub, eliminated);
+        }
+        SetLiteralExpr::SetFloats(v) => v.iter_mut().for_each(|e| rename_float(e, eliminated)),
+        SetLiteralExpr::SetInts(v) => v.iter_mut().for_each(|e| rename_int(e, eliminated)),
+    }
+}
+fn rename_var_decl(d: &mut VarDeclItem, eliminated: &HashMap<String, String>) {
+    match d {
+        VarDeclItem::Bool { expr, .. } => expr.iter_mut().for_each(|e| rename_bool(e, eliminated)),
+        VarDeclItem::Int { expr, .. }
+        | VarDeclItem::IntInRange { expr, .. }
+        | VarDeclItem::IntInSet { expr, .. } => {
+            expr.iter_mut().for_each(|e| rename_int(e, eliminated))
+        }
+        VarDeclItem::Float { expr, .. } | VarDeclItem::BoundedFloat { expr, .. } => {
+            expr.iter_mut().for_each(|e| rename_float(e, eliminated))
+        }
+        VarDeclItem::SetOfInt { expr, .. }
+        | VarDeclItem::SubSetOfIntSet { expr, .. }
+        | VarDeclItem::SubSetOfIntRange { expr, .. } => {
+            expr.iter_mut().for_each(|e| rename_set(e, eliminated))
+        }
+        VarDeclItem::ArrayOfBool { array_expr, .. } => match array_expr {
+            Some(ArrayOfBoolExpr::Array(v)) => {
+                v.iter_mut().for_each(|e| rename_bool(e, eliminated))
+            }
+            Some(ArrayOfBoolExpr::VarParIdentifier(_)) | None => {}
+        },
+        VarDeclItem::ArrayOfInt { array_expr, .. }
+        | VarDeclItem::ArrayOfIntInRange { array_expr, .. }
+        | VarDeclItem::ArrayOfIntInSet { array_expr, .. } => match array_expr {
+            Some(ArrayOfIntExpr::Array(v)) => v.iter_mut().for_each(|e| rename_int(e, eliminated)),
+            Some(ArrayOfIntExpr::VarParIdentifier(_)) | None => {}
+        },
+        VarDeclItem::ArrayOfFloat { array_expr, .. }
+        | VarDeclItem::ArrayOfBoundedFloat { array_expr, .. } => match array_expr {
+            Some(ArrayOfFloatExpr::Array(v)) => {
+                v.iter_mut().for_each(|e| rename_float(e, eliminated))
+            }
+            Some(ArrayOfFloatExpr::VarParIdentifier(_)) | None => {}
+        },
+        VarDeclItem::ArrayOfSet { array_expr, .. }
+        | VarDeclItem::ArrayOfSubSetOfIntRange { array_expr, .. }
+        | VarDeclItem::ArrayOfSubSetOfIntSet { array_expr, .. } => match array_expr {
+            Some(ArrayOfSetExpr::Array(v)) => v.iter_mut().for_each(|e| rename_set(e, eliminated)),
+            Some(ArrayOfSetExpr::VarParIdentifier(_)) | None => {}
+        },
+    }
+}
+fn rename_goal(goal: &mut Goal, eliminated: &HashMap<String, String>) {
+    match goal {
+        Goal::Satisfy => {}
+        Goal::OptimizeBool(_, e) => rename_bool(e, eliminated),
+        Goal::OptimizeInt(_, e) => rename_int(e, eliminated),
+        Goal::OptimizeFloat(_, e) => rename_float(e, eliminated),
+        Goal::OptimizeSet(_, e) => rename_set(e, eliminated),
+    }
+}
+
+#[test]
+fn test_eliminate() {
+    use std::str::FromStr;
+    let input = [
+        "int: k = 3;",
+        "var int: x;",
+        "var 1..5: y :: output_var = x;",
+        "var {2,3,4,7}: z;",
+        "var int: u = k;",
+        "var bool: a;",
+        "var bool: b :: output_var = a;",
+        "var 1..2: e;",
+        "var 3..4: f;",
+        "array [1..2] of var int: arr :: output_array([1..2]) = [y,z];",
+        "constraint int_eq(z,y);",
+        "constraint int_eq(e,f);",
+        "constraint int_le(u,z);",
+        "constraint bool_clause([b],[]);",
+        "solve maximize y;",
+    ];
+    let mut stmts: Vec<_> = input
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (i + 1, Stmt::from_str(s).unwrap()))
+        .collect();
+    let aliases = eliminate(&mut stmts);
+    let alias = |id: &str, representative: &str, output| Alias {
+        id: id.to_string(),
+        representative: representative.to_string(),
+        output,
+    };
+    assert_eq!(
+        aliases,
+        [
+            alias("y", "x", true),
+            alias("z", "x", false),
+            alias("b", "a", true),
+        ]
+    );
+    let expected = [
+        "int: k = 3;",
+        "var 2..4: x;",
+        "var int: u = k;",
+        "var bool: a;",
+        "var 1..2: e;",
+        "var 3..4: f;",
+        "array [1..2] of var int: arr :: output_array([1..2]) = [x,x];",
+        "constraint int_eq(e,f);",
+        "constraint int_le(u,x);",
+        "constraint bool_clause([a],[]);",
+        "solve maximize x;",
+    ];
+    let expected: Vec<_> = expected
+        .iter()
+        .map(|s| Stmt::from_str(s).unwrap())
+        .collect();
+    let stmts: Vec<_> = stmts.into_iter().map(|(_, s)| s).collect();
+    assert_eq!(stmts, expected);
+
+    // the value of a parameter assigned to an eliminated variable restricts the representative
+    let input = [
+        "int: k = 3;",
+        "bool: p = true;",
+        "var 1..5: x;",
+        "var int: z = k;",
+        "var bool: a;",
+        "var bool: b = p;",
+        "constraint int_eq(z,x);",
+        "constraint bool_eq(a,b);",
+        "solve satisfy;",
+    ];
+    let mut stmts: Vec<_> = input
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (i + 1, Stmt::from_str(s).unwrap()))
+        .collect();
+    let aliases = eliminate(&mut stmts);
+    assert_eq!(aliases, [alias("z", "x", false), alias("b", "a", false)]);
+    let expected: Vec<_> = [
+        "int: k = 3;",
+        "bool: p = true;",
+        "var 3..3: x = 3;",
+        "var bool: a = true;",
+        "solve satisfy;",
+    ]
+    .iter()
+    .map(|s| Stmt::from_str(s).unwrap())
+    .collect();
+    let stmts: Vec<_> = stmts.into_iter().map(|(_, s)| s).collect();
+    assert_eq!(stmts, expected);
+}
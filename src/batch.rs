@@ -0,0 +1,211 @@
+//! Conversion of many FlatZinc files in parallel.
+use crate::{compression::Output, input::Input, Options};
+use anyhow::{bail, Result};
+use rayon::prelude::*;
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// Options for converting a batch of files.
+#[derive(Clone, Debug, Default)]
+pub struct BatchOptions {
+    /// Mirror the input files into this directory instead of writing next to them
+    pub out_dir: Option<PathBuf>,
+    /// Compress the generated facts with gzip
+    pub gzip: bool,
+    /// Number of threads, 0 uses one per core
+    pub threads: usize,
+    pub options: Options,
+}
+
+/// Result of converting a single file.
+#[derive(Debug)]
+pub enum Outcome {
+    Success,
+    Warnings(usize),
+    Failure(anyhow::Error),
+}
+
+/// Outcomes of all files of a batch, in the order the files were found.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub results: Vec<(PathBuf, Outcome)>,
+}
+impl Summary {
+    pub fn successes(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::Success))
+    }
+    pub fn with_warnings(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::Warnings(_)))
+    }
+    pub fn failures(&self) -> usize {
+        self.count(|o| matches!(o, Outcome::Failure(_)))
+    }
+    fn count(&self, f: impl Fn(&Outcome) -> bool) -> usize {
+        self.results.iter().filter(|(_, o)| f(o)).count()
+    }
+}
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (path, outcome) in &self.results {
+            match outcome {
+                Outcome::Success => {}
+                Outcome::Warnings(n) => writeln!(f, "{}: {n} warning(s)", path.display())?,
+                Outcome::Failure(err) => writeln!(f, "{}: {err}", path.display())?,
+            }
+        }
+        write!(
+            f,
+            "{} file(s): {} converted, {} with warnings, {} failed",
+            self.results.len(),
+            self.successes(),
+            self.with_warnings(),
+            self.failures()
+        )
+    }
+}
+
+/// Converts the given files and all FlatZinc files found in the given directories.
+pub fn run(paths: &[PathBuf], opts: &BatchOptions) -> Result<Summary> {
+    let mut jobs = vec![];
+    for path in paths {
+        if path.is_dir() {
+            collect_files(path, path, opts, &mut jobs)?;
+        } else {
+            let name = path.file_name().map(Path::new).unwrap_or(path);
+            jobs.push((path.clone(), output_path(path, name, opts)));
+        }
+    }
+    check_clashes(&jobs)?;
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(opts.threads)
+        .build()?;
+    let results = pool.install(|| {
+        jobs.into_par_iter()
+            .map(|(input, output)| {
+                let outcome = match convert(&input, &output, opts) {
+                    Ok(0) => Outcome::Success,
+                    Ok(n) => Outcome::Warnings(n),
+                    Err(err) => Outcome::Failure(err),
+                };
+                (input, outcome)
+            })
+            .collect()
+    });
+    Ok(Summary { results })
+}
+
+/// Converts a single file, removing incomplete output on failure.
+fn convert(input: &Path, output: &Path, opts: &BatchOptions) -> Result<usize> {
+    if let Some(dir) = output.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut out = Output::create(Some(output), opts.gzip)?;
+    let res = (move || {
+        let translator = Input::open(input)?.translate(&mut out, opts.options.clone())?;
+        translator.finish()?;
+        out.finish()?;
+        Ok(translator.warnings())
+    })();
+    if res.is_err() {
+        fs::remove_file(output).ok();
+    }
+    res
+}
+
+/// Fails if two inputs would be converted into the same output file,
+/// e.g. `a/model.fzn` and `b/model.fzn` given explicitly with an output directory.
+fn check_clashes(jobs: &[(PathBuf, PathBuf)]) -> Result<()> {
+    let mut outputs = HashMap::new();
+    for (input, output) in jobs {
+        if let Some(other) = outputs.insert(output, input) {
+            bail!(
+                "{} and {} would both be written to {}",
+                other.display(),
+                input.display(),
+                output.display()
+            )
+        }
+    }
+    Ok(())
+}
+
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    opts: &BatchOptions,
+    jobs: &mut Vec<(PathBuf, PathBuf)>,
+) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|e| e.path());
+    for entry in entries {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(root, &path, opts, jobs)?;
+        } else if fzn_stem(&path).is_some() {
+            let relative = path.strip_prefix(root)?;
+            jobs.push((path.clone(), output_path(&path, relative, opts)));
+        }
+    }
+    Ok(())
+}
+
+/// File name without the `.fzn`, `.fzn.gz` or `.fzn.xz` extension.
+fn fzn_stem(path: &Path) -> Option<&str> {
+    let name = path.file_name()?.to_str()?;
+    [".fzn", ".fzn.gz", ".fzn.xz"]
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext))
+}
+
+/// Sibling `.lp` file of the input, or the relative path below the output directory.
+fn output_path(input: &Path, relative: &Path, opts: &BatchOptions) -> PathBuf {
+    let ext = if opts.gzip { "lp.gz" } else { "lp" };
+    let path = match &opts.out_dir {
+        Some(dir) => dir.join(relative),
+        None => input.to_path_buf(),
+    };
+    match fzn_stem(&path) {
+        Some(stem) => path.with_file_name(format!("{stem}.{ext}")),
+        None => path.with_extension(ext),
+    }
+}
+
+#[test]
+fn test_output_path() {
+    let mut opts = BatchOptions::default();
+    let input = Path::new("models/a/x.fzn.xz");
+    assert_eq!(output_path(input, input, &opts), Path::new("models/a/x.lp"));
+    opts.out_dir = Some("out".into());
+    opts.gzip = true;
+    assert_eq!(
+        output_path(input, Path::new("a/x.fzn.xz"), &opts),
+        Path::new("out/a/x.lp.gz")
+    );
+}
+
+#[test]
+fn test_clashes() {
+    let dir = std::env::temp_dir().join(format!("fzn2lp-batch-{}", std::process::id()));
+    for sub in ["a", "b"] {
+        fs::create_dir_all(dir.join(sub)).unwrap();
+        fs::write(dir.join(sub).join("model.fzn"), "solve satisfy;").unwrap();
+    }
+    let opts = BatchOptions {
+        out_dir: Some(dir.join("out")),
+        ..Default::default()
+    };
+    let paths = [dir.join("a/model.fzn"), dir.join("b/model.fzn")];
+    let err = run(&paths, &opts).unwrap_err();
+    assert!(err.to_string().contains("would both be written to"));
+    // both directories are mirrored into the same output directory
+    assert!(run(&[dir.join("a"), dir.join("b")], &opts).is_err());
+    assert!(!dir.join("out").exists());
+    let summary = run(std::slice::from_ref(&dir), &opts).unwrap();
+    assert_eq!(summary.successes(), 2);
+    assert!(dir.join("out/a/model.lp").exists());
+    assert!(dir.join("out/b/model.lp").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -1,50 +1,285 @@
-use anyhow::Result;
-use clap::Parser;
-use fzn2lp::{write_fz_stmt, FlatZincError};
+use anyhow::{bail, Result};
+use clap::{Args, Parser, Subcommand};
+use fzn2lp::{
+    batch::{self, BatchOptions},
+    compression::{self, Output},
+    encoding, format,
+    input::Input,
+    reverse, IntRangePolicy, Options, OutputMode, StatementOrder,
+};
 use log::error;
 use std::{
-    fs,
-    io::{self, BufRead},
-    path::PathBuf,
+    ffi::OsString,
+    path::{Path, PathBuf},
 };
 
 /// Convert FlatZinc to ASP facts
 #[derive(Parser, Debug)]
 #[clap(name = "fzn2lp")]
-#[command(version, author)]
+#[command(version, author, args_conflicts_with_subcommands = true)]
 struct Opt {
-    /// Input file in flatzinc format
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Input file in flatzinc format, may be compressed with gzip or xz
     #[arg(name = "FILE")]
     file: Option<PathBuf>,
+
+    /// Write the facts to FILE instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Compress the output with gzip, implied by an output file ending in .gz
+    #[arg(long)]
+    gzip: bool,
+
+    #[command(flatten)]
+    translation: TranslationOpt,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Convert many files in parallel, each into a sibling .lp file
+    Batch {
+        /// FlatZinc files, or directories searched for .fzn, .fzn.gz and .fzn.xz files
+        #[arg(name = "PATH", required = true)]
+        paths: Vec<PathBuf>,
+
+        /// Mirror the inputs into DIR instead of writing next to them
+        #[arg(long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+
+        /// Number of threads, defaults to one per core
+        #[arg(short = 'j', long, default_value_t = 0)]
+        threads: usize,
+
+        /// Compress the outputs with gzip
+        #[arg(long)]
+        gzip: bool,
+
+        #[command(flatten)]
+        translation: TranslationOpt,
+    },
+    /// Rewrite a FlatZinc model in canonical form and legal statement order
+    Fmt {
+        /// Input file in flatzinc format, may be compressed with gzip or xz
+        #[arg(name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Write the model to FILE instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Reconstruct a FlatZinc model from facts
+    Reverse {
+        /// Input file with facts, may be compressed with gzip or xz
+        #[arg(name = "FILE")]
+        file: Option<PathBuf>,
+
+        /// Write the model to FILE instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// List the bundled ASP encodings of the FlatZinc builtins
+    Encoding {
+        /// Print the encodings instead of listing them
+        #[arg(long)]
+        print: bool,
+
+        /// Parts to print, defaults to all
+        #[arg(name = "NAME", requires = "print")]
+        names: Vec<String>,
+    },
+}
+
+#[derive(Args, Debug)]
+struct TranslationOpt {
+    /// How to handle integers outside of clingo's 32-bit range
+    #[arg(long, value_enum, default_value_t = IntRangePolicy::Warn)]
+    int_range: IntRangePolicy,
+
+    /// Treat statements in wrong order as errors
+    #[arg(long, conflicts_with = "lenient")]
+    strict: bool,
+
+    /// Accept models without solve item
+    #[arg(long)]
+    lenient: bool,
+
+    /// Output format of constraints and domains
+    #[arg(long, value_enum, default_value_t = OutputMode::Facts)]
+    mode: OutputMode,
+
+    /// Write the domains of integer variables as dom/2 facts
+    #[arg(long)]
+    domains: bool,
+
+    /// Also write the order encoding helpers dom_min/2, dom_max/2 and dom_next/3
+    #[arg(long, requires = "domains")]
+    order_encoding: bool,
+
+    /// Move unary constraints into the domains of their variables
+    #[arg(long)]
+    presolve: bool,
+
+    /// Replace variables equal to other variables by a representative
+    #[arg(long)]
+    eliminate_aliases: bool,
+
+    /// Write identifiers as constants instead of strings where they are valid ASP symbols
+    #[arg(long)]
+    constants: bool,
+
+    /// Translate the statements of a file on all cores
+    #[arg(long)]
+    parallel: bool,
+
+    /// Write constant sets of integers as elem terms, up to MAX elements, and their set_size
+    #[arg(
+        long,
+        value_name = "MAX",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "10000"
+    )]
+    expand_sets: Option<usize>,
+
+    /// Also write the dimensions of output arrays and the coordinates of their elements
+    #[arg(long)]
+    output_array_elements: bool,
+}
+impl TranslationOpt {
+    fn options(&self) -> Options {
+        let order = if self.strict {
+            StatementOrder::Strict
+        } else if self.lenient {
+            StatementOrder::Lenient
+        } else {
+            StatementOrder::Warn
+        };
+        Options {
+            int_range: self.int_range,
+            order,
+            mode: self.mode,
+            domains: self.domains,
+            order_encoding: self.order_encoding,
+            presolve: self.presolve,
+            eliminate_aliases: self.eliminate_aliases,
+            constants: self.constants,
+            parallel: self.parallel,
+            expand_sets: self.expand_sets,
+            output_array_elements: self.output_array_elements,
+        }
+    }
 }
 
 fn main() {
-    env_logger::builder().format_timestamp(None).init();
+    // warnings are shown unless RUST_LOG asks for less
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"))
+        .format_timestamp(None)
+        .init();
     if let Err(err) = run() {
         error!("{}", err);
         std::process::exit(1);
     }
 }
+/// Names of the subcommands, which clap prefers over an input file of the same name.
+const COMMANDS: [&str; 4] = ["batch", "fmt", "reverse", "encoding"];
+
 fn run() -> Result<()> {
-    let mut stdin_lock = io::stdin().lock();
-    let mut file_reader;
-    let input: &mut dyn BufRead = match Opt::parse().file {
-        Some(path) => {
-            let file = fs::File::open(path)?;
-            file_reader = io::BufReader::new(file);
-            &mut file_reader
+    let args: Vec<OsString> = std::env::args_os().collect();
+    // an existing file named like a subcommand in first position is translated, as before there
+    // were subcommands, unless the arguments only make sense for the subcommand like `fmt fmt`
+    let opt = match args.get(1) {
+        Some(first) if COMMANDS.iter().any(|c| first == c) && Path::new(first).is_file() => {
+            let mut file_args = args.clone();
+            file_args[1] = Path::new(".").join(first).into_os_string();
+            Opt::try_parse_from(file_args).unwrap_or_else(|_| Opt::parse_from(args))
         }
-        None => &mut stdin_lock,
+        _ => Opt::parse_from(args),
     };
+    match opt.command {
+        Some(Command::Batch {
+            paths,
+            out_dir,
+            threads,
+            gzip,
+            translation,
+        }) => return run_batch(paths, out_dir, threads, gzip, translation),
+        Some(Command::Fmt { file, output }) => return run_fmt(file, output),
+        Some(Command::Reverse { file, output }) => return run_reverse(file, output),
+        Some(Command::Encoding { print, names }) => return print_encoding(print, &names),
+        None => {}
+    }
 
-    let mut out = std::io::stdout();
-    let mut level = 1;
-    let mut constraint_counter = 0;
-    for line in input.lines() {
-        write_fz_stmt(&mut out, &line?, &mut constraint_counter, &mut level)?;
+    let input = match &opt.file {
+        Some(path) => Input::open(path)?,
+        None => Input::stdin()?,
+    };
+    let mut out = Output::create(opt.output.as_deref(), opt.gzip)?;
+    let translator = input.translate(&mut out, opt.translation.options())?;
+    out.finish()?;
+    if let Some(report) = translator.presolve_report() {
+        eprintln!("{report}");
     }
-    if level < 5 {
-        return Err(FlatZincError::NoSolveItem.into());
+    translator.finish()
+}
+fn run_batch(
+    paths: Vec<PathBuf>,
+    out_dir: Option<PathBuf>,
+    threads: usize,
+    gzip: bool,
+    translation: TranslationOpt,
+) -> Result<()> {
+    let opts = BatchOptions {
+        out_dir,
+        gzip,
+        threads,
+        options: translation.options(),
+    };
+    let summary = batch::run(&paths, &opts)?;
+    println!("{summary}");
+    if summary.failures() > 0 {
+        bail!("{} file(s) failed", summary.failures());
+    }
+    Ok(())
+}
+fn run_fmt(file: Option<PathBuf>, output: Option<PathBuf>) -> Result<()> {
+    let input = match &file {
+        Some(path) => compression::open_input(path)?,
+        None => compression::stdin()?,
+    };
+    let mut out = Output::create(output.as_deref(), false)?;
+    format::format(input, &mut out)?;
+    out.finish()
+}
+fn run_reverse(file: Option<PathBuf>, output: Option<PathBuf>) -> Result<()> {
+    let input = match &file {
+        Some(path) => compression::open_input(path)?,
+        None => compression::stdin()?,
+    };
+    let mut out = Output::create(output.as_deref(), false)?;
+    reverse::reverse(input, &mut out)?;
+    out.finish()
+}
+fn print_encoding(print: bool, names: &[String]) -> Result<()> {
+    if !print {
+        for (name, _) in encoding::ENCODINGS {
+            println!("{name}");
+        }
+        return Ok(());
+    }
+    if names.is_empty() {
+        print!("{}", encoding::full());
+        return Ok(());
+    }
+    let mut parts = vec![];
+    for name in names {
+        match encoding::get(name) {
+            Some(encoding) => parts.push(encoding),
+            None => bail!("Unknown encoding {name}"),
+        }
     }
+    print!("{}", parts.join("\n"));
     Ok(())
 }
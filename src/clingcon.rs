@@ -0,0 +1,201 @@
+//! Output of linear constraints and integer domains as clingcon theory atoms.
+//!
+//! Reified constraints are tied to the atom `true(B)` of their Boolean variable `B`, as in the
+//! bundled encodings.
+use crate::{
+    domain_elements, fits_clingo_int, ident, int_bound,
+    linear::{linear, Linear, Literal, Reification, Relation},
+    Options,
+};
+use anyhow::Result;
+use flatzinc::{ConstraintItem, IntExpr, ParDeclItem, VarDeclItem};
+use std::{collections::HashMap, io::Write};
+
+/// Writes a linear constraint as `&sum` atom, returns false if the constraint is not linear.
+pub(crate) fn write_constraint(
+    mut buf: impl Write,
+    c: &ConstraintItem,
+    opts: &Options,
+    params: &HashMap<String, ParDeclItem>,
+) -> Result<bool> {
+    let Some(lin) = linear(c, params) else {
+        return Ok(false);
+    };
+    if lin.terms.is_empty() || !lin.fits_clingo() {
+        return Ok(false);
+    }
+    match &lin.reification {
+        Reification::None
+        | Reification::Equiv(Literal::Const(true))
+        | Reification::Imp(Literal::Const(true)) => {
+            writeln!(buf, "{}.", sum(&lin, lin.relation, opts))?;
+        }
+        Reification::Equiv(Literal::Const(false)) => {
+            writeln!(buf, "{}.", sum(&lin, lin.relation.negate(), opts))?;
+        }
+        Reification::Imp(Literal::Const(false)) => {}
+        Reification::Equiv(Literal::Var(b)) => {
+            let b = ident(b, opts);
+            writeln!(buf, "{} :- true({b}).", sum(&lin, lin.relation, opts))?;
+            writeln!(
+                buf,
+                "{} :- not true({b}).",
+                sum(&lin, lin.relation.negate(), opts)
+            )?;
+        }
+        Reification::Imp(Literal::Var(b)) => {
+            let b = ident(b, opts);
+            writeln!(buf, "{} :- true({b}).", sum(&lin, lin.relation, opts))?;
+        }
+    }
+    Ok(true)
+}
+
+/// Writes the domain and the assigned value of an integer variable.
+///
+/// Domains and values with integers outside of clingo's range are only written as facts.
+pub(crate) fn write_var_decl(
+    mut buf: impl Write,
+    item: &VarDeclItem,
+    opts: &Options,
+    params: &HashMap<String, ParDeclItem>,
+) -> Result<()> {
+    let (id, expr) = match item {
+        VarDeclItem::Int { id, expr, .. } => (id, expr),
+        VarDeclItem::IntInRange {
+            id, lb, ub, expr, ..
+        } => {
+            let (lb, ub) = (int_bound(*lb, opts), int_bound(*ub, opts));
+            if let (Some(lb), Some(ub)) = (lb.fitting(), ub.fitting()) {
+                writeln!(buf, "&dom{{{lb}..{ub}}} = {}.", ident(id, opts))?;
+            }
+            (id, expr)
+        }
+        VarDeclItem::IntInSet { id, set, expr, .. } => {
+            let elements: Vec<_> = domain_elements(set, opts).collect();
+            if elements.is_empty() {
+                writeln!(buf, ":- #true.")?;
+            } else if elements.iter().all(|i| fits_clingo_int(**i)) {
+                let elements: Vec<_> = elements.iter().map(|i| i.to_string()).collect();
+                writeln!(buf, "&dom{{{}}} = {}.", elements.join(";"), ident(id, opts))?;
+            }
+            (id, expr)
+        }
+        _ => return Ok(()),
+    };
+    let id = ident(id, opts);
+    let value = match expr {
+        Some(IntExpr::Int(i)) => Some(*i),
+        Some(IntExpr::VarParIdentifier(y)) => match params.get(y) {
+            Some(ParDeclItem::Int { int, .. }) => Some(*int),
+            _ => {
+                writeln!(buf, "&sum{{ {id}; -1*{} }} = 0.", ident(y, opts))?;
+                None
+            }
+        },
+        None => None,
+    };
+    if let Some(value) = value.filter(|i| fits_clingo_int(*i)) {
+        writeln!(buf, "&sum{{ {id} }} = {value}.")?;
+    }
+    Ok(())
+}
+
+fn sum(lin: &Linear, relation: Relation, opts: &Options) -> String {
+    let terms: Vec<_> = lin
+        .terms
+        .iter()
+        .map(|(a, x)| match a {
+            1 => ident(x, opts).to_string(),
+            a => format!("{a}*{}", ident(x, opts)),
+        })
+        .collect();
+    format!(
+        "&sum{{ {} }} {} {}",
+        terms.join("; "),
+        relation.symbol(),
+        lin.rhs
+    )
+}
+
+#[test]
+fn test_clingcon() {
+    use crate::{translate, OutputMode};
+    let input = "int: k = 2;\n\
+                 var 1..3: x;\n\
+                 var {1,3}: y = x;\n\
+                 var bool: r;\n\
+                 constraint int_lin_le_reif([1,-2],[x,y],k,r);\n\
+                 constraint int_plus(x,1,y);\n\
+                 constraint int_times(x,y,x);\n\
+                 solve satisfy;\n";
+    let opts = Options {
+        mode: OutputMode::Clingcon,
+        ..Options::default()
+    };
+    let mut res = vec![];
+    translate(input.as_bytes(), &mut res, opts).unwrap();
+    let res = String::from_utf8(res).unwrap();
+    let theory: Vec<_> = res
+        .lines()
+        .filter(|l| l.starts_with('&') || l.starts_with("constraint("))
+        .collect();
+    assert_eq!(
+        theory,
+        [
+            "&dom{1..3} = \"x\".",
+            "&dom{1;3} = \"y\".",
+            "&sum{ \"y\"; -1*\"x\" } = 0.",
+            "&sum{ \"x\"; -2*\"y\" } <= 2 :- true(\"r\").",
+            "&sum{ \"x\"; -2*\"y\" } > 2 :- not true(\"r\").",
+            "&sum{ \"x\"; -1*\"y\" } = -1.",
+            "constraint(c3,\"int_times\").",
+        ]
+    );
+
+    // integers outside of clingo's range are only written as facts
+    let theory = |input: &str, int_range| {
+        let opts = Options {
+            mode: OutputMode::Clingcon,
+            int_range,
+            ..Options::default()
+        };
+        let mut res = vec![];
+        translate(input.as_bytes(), &mut res, opts).unwrap();
+        String::from_utf8(res)
+            .unwrap()
+            .lines()
+            .filter(|l| l.starts_with('&') || l.starts_with("constraint("))
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    let input = "var -3000000000..5: x;\n\
+                 var {1,3000000000}: y;\n\
+                 var int: z = 3000000000;\n\
+                 constraint int_lin_le([3000000000,1],[x,y],5);\n\
+                 constraint int_lin_le([1],[x],3000000000);\n\
+                 constraint int_lin_le([1,1,1],[x,2000000000,2000000000],5);\n\
+                 solve satisfy;\n";
+    assert_eq!(
+        theory(input, crate::IntRangePolicy::String),
+        [
+            "constraint(c1,\"int_lin_le\").",
+            "constraint(c2,\"int_lin_le\").",
+            "constraint(c3,\"int_lin_le\").",
+        ]
+    );
+    let input = "var -3000000000..5: x;\n\
+                 var {1,3000000000}: y;\n\
+                 constraint int_lin_le([1],[x],30);\n\
+                 constraint int_lin_le([1,1,1],[x,2000000000,2000000000],5);\n\
+                 solve satisfy;\n";
+    assert_eq!(
+        theory(input, crate::IntRangePolicy::Clamp),
+        [
+            "&dom{-2147483648..5} = \"x\".",
+            "&dom{1} = \"y\".",
+            "&sum{ \"x\" } <= 30.",
+            "constraint(c2,\"int_lin_le\").",
+        ]
+    );
+}
@@ -0,0 +1,166 @@
+//! Transparent handling of gzip and xz compressed input and output files.
+use anyhow::Result;
+use flate2::{bufread::MultiGzDecoder, write::GzEncoder, Compression};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+use xz2::bufread::XzDecoder;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+/// Opens a possibly compressed input file.
+///
+/// The compression is detected from the magic bytes of the file, or from the extensions
+/// `.gz` and `.xz` if the file is too short to tell.
+pub fn open_input(path: &Path) -> Result<Box<dyn BufRead>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let format = path_format(path, reader.fill_buf()?);
+    Ok(decompress(reader, format))
+}
+
+/// Whether a file is compressed, judged by its first bytes or by its extension like in
+/// [`open_input`].
+pub(crate) fn is_compressed(path: &Path, start: &[u8]) -> bool {
+    path_format(path, start).is_some()
+}
+
+/// The compression of a file starting with `start`, by the extension only if the file is
+/// shorter than the magic bytes of the format it names.
+fn path_format(path: &Path, mut start: &[u8]) -> Option<Format> {
+    let len = start.len();
+    if let Ok(Some(format)) = detect_format(&mut start) {
+        return Some(format);
+    }
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("gz") if len < GZIP_MAGIC.len() => Some(Format::Gzip),
+        Some("xz") if len < XZ_MAGIC.len() => Some(Format::Xz),
+        _ => None,
+    }
+}
+
+/// Wraps stdin in a decoder if it starts with the magic bytes of gzip or xz.
+pub fn stdin() -> Result<Box<dyn BufRead>> {
+    let mut reader = io::stdin().lock();
+    let format = detect_format(&mut reader)?;
+    Ok(decompress(reader, format))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Gzip,
+    Xz,
+}
+
+fn detect_format(reader: &mut impl BufRead) -> Result<Option<Format>> {
+    let buf = reader.fill_buf()?;
+    Ok(if buf.starts_with(GZIP_MAGIC) {
+        Some(Format::Gzip)
+    } else if buf.starts_with(XZ_MAGIC) {
+        Some(Format::Xz)
+    } else {
+        None
+    })
+}
+
+fn decompress<R: BufRead + 'static>(reader: R, format: Option<Format>) -> Box<dyn BufRead> {
+    match format {
+        Some(Format::Gzip) => Box::new(BufReader::new(MultiGzDecoder::new(reader))),
+        Some(Format::Xz) => Box::new(BufReader::new(XzDecoder::new_multi_decoder(reader))),
+        None => Box::new(reader),
+    }
+}
+
+/// Destination of the generated facts, optionally gzip compressed.
+pub enum Output {
+    Plain(Box<dyn Write>),
+    Gzip(GzEncoder<Box<dyn Write>>),
+}
+impl Output {
+    /// Creates the output file, or writes to stdout if no path is given.
+    ///
+    /// The output is compressed if `gzip` is set or the file name ends with `.gz`. Both files and
+    /// stdout are block-buffered, stdout stays locked until the output is dropped.
+    pub fn create(path: Option<&Path>, gzip: bool) -> Result<Output> {
+        let (writer, gzip): (Box<dyn Write>, _) = match path {
+            Some(path) => (
+                Box::new(BufWriter::new(File::create(path)?)),
+                gzip || path.extension().is_some_and(|e| e == "gz"),
+            ),
+            None => (Box::new(BufWriter::new(io::stdout().lock())), gzip),
+        };
+        Ok(if gzip {
+            Output::Gzip(GzEncoder::new(writer, Compression::default()))
+        } else {
+            Output::Plain(writer)
+        })
+    }
+    /// Writes the remaining compressed data and flushes the output.
+    pub fn finish(self) -> Result<()> {
+        match self {
+            Output::Plain(mut w) => w.flush()?,
+            Output::Gzip(e) => e.finish()?.flush()?,
+        }
+        Ok(())
+    }
+}
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::Plain(w) => w.write(buf),
+            Output::Gzip(e) => e.write(buf),
+        }
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::Plain(w) => w.flush(),
+            Output::Gzip(e) => e.flush(),
+        }
+    }
+}
+
+#[test]
+fn test_decompress() {
+    use std::io::Read;
+    let input = "solve satisfy;\n";
+    let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+    gz.write_all(input.as_bytes()).unwrap();
+    let gz = gz.finish().unwrap();
+    let mut xz = xz2::write::XzEncoder::new(Vec::new(), 6);
+    xz.write_all(input.as_bytes()).unwrap();
+    let xz = xz.finish().unwrap();
+    for data in [input.as_bytes().to_vec(), gz, xz] {
+        let mut reader = io::Cursor::new(data);
+        let format = detect_format(&mut reader).unwrap();
+        let mut res = String::new();
+        decompress(reader, format).read_to_string(&mut res).unwrap();
+        assert_eq!(res, input);
+    }
+}
+
+#[test]
+fn test_path_format() {
+    use std::io::Read;
+    let path = Path::new("model.fzn.gz");
+    assert_eq!(path_format(path, b"solve satisfy;\n"), None);
+    assert_eq!(path_format(path, GZIP_MAGIC), Some(Format::Gzip));
+    assert_eq!(path_format(path, b""), Some(Format::Gzip));
+    assert_eq!(
+        path_format(Path::new("model.fzn"), XZ_MAGIC),
+        Some(Format::Xz)
+    );
+    assert_eq!(
+        path_format(Path::new("model.xz"), b"\xfd7z"),
+        Some(Format::Xz)
+    );
+
+    let path = std::env::temp_dir().join(format!("fzn2lp-plain-{}.fzn.gz", std::process::id()));
+    std::fs::write(&path, "solve satisfy;\n").unwrap();
+    let mut res = String::new();
+    let read = open_input(&path).and_then(|mut input| Ok(input.read_to_string(&mut res)?));
+    std::fs::remove_file(&path).unwrap();
+    read.unwrap();
+    assert_eq!(res, "solve satisfy;\n");
+}
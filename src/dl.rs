@@ -0,0 +1,242 @@
+//! Output of difference constraints and integer bounds as clingo-dl theory atoms.
+//!
+//! A constraint `x - y <= k` becomes `&diff{ "x"-"y" } <= k`, bounds use the node `0`.
+//! Reified constraints are tied to the atom `true(B)` of their Boolean variable `B`, as in the
+//! bundled encodings.
+use crate::{
+    fits_clingo_int, ident, int_bound,
+    linear::{linear, Linear, Literal, Reification, Relation},
+    Options,
+};
+use anyhow::Result;
+use flatzinc::{ConstraintItem, IntExpr, ParDeclItem, VarDeclItem};
+use std::{collections::HashMap, io::Write};
+
+/// The atom `&diff{ u-v } <= k`.
+struct Diff {
+    u: String,
+    v: String,
+    k: i128,
+}
+impl std::fmt::Display for Diff {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "&diff{{ {}-{} }} <= {}", self.u, self.v, self.k)
+    }
+}
+
+/// Writes a difference constraint as `&diff` atoms, returns false if it is none.
+pub(crate) fn write_constraint(
+    mut buf: impl Write,
+    c: &ConstraintItem,
+    opts: &Options,
+    params: &HashMap<String, ParDeclItem>,
+) -> Result<bool> {
+    let Some(lin) = linear(c, params) else {
+        return Ok(false);
+    };
+    let (pos, neg) = (
+        diffs(&lin, lin.relation, opts),
+        diffs(&lin, lin.relation.negate(), opts),
+    );
+    let (rules, body) = match (&lin.reification, pos, neg) {
+        (
+            Reification::None
+            | Reification::Equiv(Literal::Const(true))
+            | Reification::Imp(Literal::Const(true)),
+            Some(pos),
+            _,
+        ) => (vec![(pos, "")], String::new()),
+        (Reification::Equiv(Literal::Const(false)), _, Some(neg)) => {
+            (vec![(neg, "")], String::new())
+        }
+        (Reification::Imp(Literal::Const(false)), Some(_), _) => (vec![], String::new()),
+        (Reification::Equiv(Literal::Var(b)), Some(pos), Some(neg)) => (
+            vec![(pos, ""), (neg, "not ")],
+            format!("true({})", ident(b, opts)),
+        ),
+        (Reification::Imp(Literal::Var(b)), Some(pos), _) => {
+            (vec![(pos, "")], format!("true({})", ident(b, opts)))
+        }
+        _ => return Ok(false),
+    };
+    for (diffs, sign) in rules {
+        for diff in diffs {
+            if body.is_empty() {
+                writeln!(buf, "{diff}.")?;
+            } else {
+                writeln!(buf, "{diff} :- {sign}{body}.")?;
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Writes the bounds and the assigned value of an integer variable.
+pub(crate) fn write_var_decl(
+    mut buf: impl Write,
+    item: &VarDeclItem,
+    opts: &Options,
+    params: &HashMap<String, ParDeclItem>,
+) -> Result<()> {
+    let (id, expr) = match item {
+        VarDeclItem::Int { id, expr, .. } => (id, expr),
+        VarDeclItem::IntInRange {
+            id, lb, ub, expr, ..
+        } => {
+            // the bounds are clamped before the lower one is negated, a lower bound of
+            // -2147483648 holds anyway and bounds outside of clingo's range are only facts
+            let x = ident(id, opts);
+            if let Some(ub) = int_bound(*ub, opts).fitting() {
+                writeln!(buf, "&diff{{ {x}-0 }} <= {ub}.")?;
+            }
+            let lb = int_bound(*lb, opts).fitting().and_then(negated);
+            if let Some(lb) = lb {
+                writeln!(buf, "&diff{{ 0-{x} }} <= {lb}.")?;
+            }
+            (id, expr)
+        }
+        _ => return Ok(()),
+    };
+    let (other, k) = match expr {
+        Some(IntExpr::Int(i)) => ("0".to_string(), *i),
+        Some(IntExpr::VarParIdentifier(y)) => match params.get(y) {
+            Some(ParDeclItem::Int { int, .. }) => ("0".to_string(), *int),
+            _ => (ident(y, opts).to_string(), 0),
+        },
+        None => return Ok(()),
+    };
+    let id = ident(id, opts);
+    if let (true, Some(neg)) = (fits_clingo_int(k), negated(k)) {
+        writeln!(buf, "&diff{{ {id}-{other} }} <= {k}.")?;
+        writeln!(buf, "&diff{{ {other}-{id} }} <= {neg}.")?;
+    }
+    Ok(())
+}
+
+/// The negated integer if it fits into a clingo integer.
+fn negated(k: i128) -> Option<i128> {
+    k.checked_neg().filter(|k| fits_clingo_int(*k))
+}
+
+/// The conjunction of difference atoms equivalent to the linear constraint with the given
+/// relation, if there is one.
+fn diffs(lin: &Linear, relation: Relation, opts: &Options) -> Option<Vec<Diff>> {
+    let node = |x: &String| ident(x, opts).to_string();
+    let (u, v) = match lin.terms.as_slice() {
+        [(1, x), (-1, y)] | [(-1, y), (1, x)] => (node(x), node(y)),
+        [(1, x)] => (node(x), "0".to_string()),
+        [(-1, y)] => ("0".to_string(), node(y)),
+        _ => return None,
+    };
+    let k = lin.rhs;
+    let diffs = match relation {
+        Relation::Le => vec![Diff { u, v, k }],
+        Relation::Gt => vec![Diff {
+            u: v,
+            v: u,
+            k: k.checked_neg()?.checked_sub(1)?,
+        }],
+        Relation::Eq => vec![
+            Diff {
+                u: u.clone(),
+                v: v.clone(),
+                k,
+            },
+            Diff {
+                u: v,
+                v: u,
+                k: k.checked_neg()?,
+            },
+        ],
+        Relation::Ne => return None,
+    };
+    // constraints with integers outside of clingo's range are written as facts
+    diffs.iter().all(|d| fits_clingo_int(d.k)).then_some(diffs)
+}
+
+#[test]
+fn test_dl() {
+    use crate::{translate, OutputMode};
+    let input = "array [1..2] of int: a = [1,-1];\n\
+                 var 0..10: s1;\n\
+                 var 0..10: s2;\n\
+                 var bool: b;\n\
+                 constraint int_lin_le(a,[s1,s2],-3);\n\
+                 constraint int_le_reif(s2,s1,b);\n\
+                 constraint int_eq(s1,4);\n\
+                 constraint int_ne(s1,s2);\n\
+                 constraint int_lin_le([2,-1],[s1,s2],0);\n\
+                 solve satisfy;\n";
+    let opts = Options {
+        mode: OutputMode::Dl,
+        ..Options::default()
+    };
+    let mut res = vec![];
+    translate(input.as_bytes(), &mut res, opts).unwrap();
+    let res = String::from_utf8(res).unwrap();
+    let theory: Vec<_> = res
+        .lines()
+        .filter(|l| l.starts_with('&') || l.starts_with("constraint("))
+        .collect();
+    assert_eq!(
+        theory,
+        [
+            "&diff{ \"s1\"-0 } <= 10.",
+            "&diff{ 0-\"s1\" } <= 0.",
+            "&diff{ \"s2\"-0 } <= 10.",
+            "&diff{ 0-\"s2\" } <= 0.",
+            "&diff{ \"s1\"-\"s2\" } <= -3.",
+            "&diff{ \"s2\"-\"s1\" } <= 0 :- true(\"b\").",
+            "&diff{ \"s1\"-\"s2\" } <= -1 :- not true(\"b\").",
+            "&diff{ \"s1\"-0 } <= 4.",
+            "&diff{ 0-\"s1\" } <= -4.",
+            "constraint(c4,\"int_ne\").",
+            "constraint(c5,\"int_lin_le\").",
+        ]
+    );
+
+    // integers outside of clingo's range are only written as facts
+    let theory = |input: &str, int_range| {
+        let opts = Options {
+            mode: OutputMode::Dl,
+            int_range,
+            ..Options::default()
+        };
+        let mut res = vec![];
+        translate(input.as_bytes(), &mut res, opts).unwrap();
+        String::from_utf8(res)
+            .unwrap()
+            .lines()
+            .filter(|l| l.starts_with('&') || l.starts_with("constraint("))
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    let input = "var -3000000000..3000000000: x;\n\
+                 var 0..5: y;\n\
+                 var int: z = 3000000000;\n\
+                 constraint int_le(x,3000000000);\n\
+                 constraint int_lin_le([1,-1],[x,y],-2147483648);\n\
+                 constraint int_lt(x,-2147483648);\n\
+                 solve satisfy;\n";
+    assert_eq!(
+        theory(input, crate::IntRangePolicy::String),
+        [
+            "&diff{ \"y\"-0 } <= 5.",
+            "&diff{ 0-\"y\" } <= 0.",
+            "constraint(c1,\"int_le\").",
+            "&diff{ \"x\"-\"y\" } <= -2147483648.",
+            "constraint(c3,\"int_lt\").",
+        ]
+    );
+    let input = "var -3000000000..3000000000: x;\n\
+                 var -2147483647..0: y;\n\
+                 solve satisfy;\n";
+    assert_eq!(
+        theory(input, crate::IntRangePolicy::Clamp),
+        [
+            "&diff{ \"x\"-0 } <= 2147483647.",
+            "&diff{ \"y\"-0 } <= 0.",
+            "&diff{ 0-\"y\" } <= 2147483647.",
+        ]
+    );
+}
@@ -0,0 +1,134 @@
+//! Pre-expanded domains of integer variables.
+//!
+//! Writes `dom(X,L..U)` for ranges and `dom(X,V)` for the values of sets, and with the order
+//! encoding helpers `dom_min(X,L)`, `dom_max(X,U)` and `dom_next(X,V,W)` for consecutive values.
+use crate::{ident, IntRangePolicy, Options};
+use anyhow::Result;
+use flatzinc::{IntExpr, ParDeclItem, VarDeclItem};
+use std::{collections::HashMap, io::Write};
+
+enum Domain {
+    Range(i128, i128),
+    Set(Vec<i128>),
+}
+
+/// Writes the domain of an integer variable, a variable without domain assigned a constant
+/// or an integer parameter has this value as domain.
+pub(crate) fn write_domain(
+    mut buf: impl Write,
+    item: &VarDeclItem,
+    opts: &Options,
+    params: &HashMap<String, ParDeclItem>,
+) -> Result<()> {
+    let bound = |i: i128| match opts.int_range {
+        IntRangePolicy::Clamp => i.clamp(i32::MIN.into(), i32::MAX.into()),
+        _ => i,
+    };
+    let (id, domain) = match item {
+        VarDeclItem::IntInRange { id, lb, ub, .. } => (id, Domain::Range(bound(*lb), bound(*ub))),
+        VarDeclItem::IntInSet { id, set, .. } => {
+            let mut set: Vec<_> = crate::domain_elements(set, opts).copied().collect();
+            set.sort_unstable();
+            set.dedup();
+            (id, Domain::Set(set))
+        }
+        VarDeclItem::Int {
+            id,
+            expr: Some(expr),
+            ..
+        } => match expr {
+            IntExpr::Int(i) => (id, Domain::Range(*i, *i)),
+            IntExpr::VarParIdentifier(p) => match params.get(p) {
+                Some(ParDeclItem::Int { int, .. }) => (id, Domain::Range(*int, *int)),
+                _ => return Ok(()),
+            },
+        },
+        _ => return Ok(()),
+    };
+    let id = ident(id, opts);
+    match &domain {
+        Domain::Range(lb, ub) if lb == ub => writeln!(buf, "dom({id},{lb}).")?,
+        Domain::Range(lb, ub) => writeln!(buf, "dom({id},{lb}..{ub}).")?,
+        Domain::Set(set) => {
+            for i in set {
+                writeln!(buf, "dom({id},{i}).")?;
+            }
+        }
+    }
+    if !opts.order_encoding {
+        return Ok(());
+    }
+    match domain {
+        Domain::Range(lb, ub) if lb <= ub => {
+            writeln!(buf, "dom_min({id},{lb}).")?;
+            writeln!(buf, "dom_max({id},{ub}).")?;
+            if lb < ub {
+                writeln!(buf, "dom_next({id},V,V+1) :- V = {lb}..{}.", ub - 1)?;
+            }
+        }
+        Domain::Set(set) if !set.is_empty() => {
+            writeln!(buf, "dom_min({id},{}).", set[0])?;
+            writeln!(buf, "dom_max({id},{}).", set[set.len() - 1])?;
+            for w in set.windows(2) {
+                writeln!(buf, "dom_next({id},{},{}).", w[0], w[1])?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[test]
+fn test_domain() {
+    use crate::translate;
+    let input = "int: k = 7;\n\
+                 var 1..3: x;\n\
+                 var {4,1,2}: y;\n\
+                 var int: z = k;\n\
+                 var int: w;\n\
+                 solve satisfy;\n";
+    let mut opts = Options {
+        domains: true,
+        ..Options::default()
+    };
+    let domains = |opts: Options| {
+        let mut res = vec![];
+        translate(input.as_bytes(), &mut res, opts).unwrap();
+        String::from_utf8(res)
+            .unwrap()
+            .lines()
+            .filter(|l| l.starts_with("dom"))
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        domains(opts.clone()),
+        [
+            "dom(\"x\",1..3).",
+            "dom(\"y\",1).",
+            "dom(\"y\",2).",
+            "dom(\"y\",4).",
+            "dom(\"z\",7).",
+        ]
+    );
+    opts.order_encoding = true;
+    assert_eq!(
+        domains(opts),
+        [
+            "dom(\"x\",1..3).",
+            "dom_min(\"x\",1).",
+            "dom_max(\"x\",3).",
+            "dom_next(\"x\",V,V+1) :- V = 1..2.",
+            "dom(\"y\",1).",
+            "dom(\"y\",2).",
+            "dom(\"y\",4).",
+            "dom_min(\"y\",1).",
+            "dom_max(\"y\",4).",
+            "dom_next(\"y\",1,2).",
+            "dom_next(\"y\",2,4).",
+            "dom(\"z\",7).",
+            "dom_min(\"z\",7).",
+            "dom_max(\"z\",7).",
+        ]
+    );
+}
@@ -0,0 +1,226 @@
+//! Reference ASP encodings giving the FlatZinc builtin constraints a meaning.
+//!
+//! The encodings work on the facts produced by this crate. `base` assigns the variables
+//! and resolves constraint arguments, the other parts define the builtins.
+
+pub const BASE: &str = include_str!("../encodings/base.lp");
+pub const INT: &str = include_str!("../encodings/int.lp");
+pub const BOOL: &str = include_str!("../encodings/bool.lp");
+pub const SET: &str = include_str!("../encodings/set.lp");
+
+/// Names and contents of the bundled encodings.
+pub const ENCODINGS: &[(&str, &str)] =
+    &[("base", BASE), ("int", INT), ("bool", BOOL), ("set", SET)];
+
+/// Returns the encoding with the given name.
+pub fn get(name: &str) -> Option<&'static str> {
+    ENCODINGS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, encoding)| *encoding)
+}
+
+/// All encodings combined into a single program.
+pub fn full() -> String {
+    ENCODINGS
+        .iter()
+        .map(|(_, encoding)| *encoding)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Every builtin is named by one of the encodings, their meaning is tested by `test_models`.
+#[test]
+fn test_builtins() {
+    let builtins = [
+        "int_eq",
+        "int_ne",
+        "int_le",
+        "int_lt",
+        "int_lin_eq",
+        "int_lin_ne",
+        "int_lin_le",
+        "int_plus",
+        "int_times",
+        "int_div",
+        "int_mod",
+        "int_pow",
+        "int_abs",
+        "int_min",
+        "int_max",
+        "array_int_element",
+        "array_var_int_element",
+        "array_int_maximum",
+        "array_int_minimum",
+        "bool_eq",
+        "bool_not",
+        "bool_le",
+        "bool_lt",
+        "bool_xor",
+        "bool_and",
+        "bool_or",
+        "bool_clause",
+        "array_bool_and",
+        "array_bool_or",
+        "array_bool_xor",
+        "bool2int",
+        "bool_lin_eq",
+        "bool_lin_le",
+        "array_bool_element",
+        "array_var_bool_element",
+        "set_in",
+        "set_card",
+        "set_eq",
+        "set_ne",
+        "set_subset",
+        "set_superset",
+        "set_union",
+        "set_intersect",
+        "set_diff",
+        "set_symdiff",
+        "array_set_element",
+        "array_var_set_element",
+        "int_eq_reif",
+        "int_lin_le_reif",
+        "bool_clause_reif",
+        "set_in_reif",
+        "int_le_imp",
+    ];
+    let full = full();
+    for builtin in builtins {
+        assert!(full.contains(&format!("\"{builtin}\"")), "{builtin}");
+    }
+    assert_eq!(get("int"), Some(INT));
+    assert_eq!(get("float"), None);
+}
+
+/// Checks whether clingo can be run, the tests using it are skipped otherwise unless
+/// `FZN2LP_REQUIRE_CLINGO` is set.
+#[cfg(test)]
+fn clingo_found() -> bool {
+    let found = std::process::Command::new("clingo")
+        .arg("--version")
+        .output()
+        .is_ok();
+    if !found {
+        assert!(
+            std::env::var_os("FZN2LP_REQUIRE_CLINGO").is_none(),
+            "clingo not found"
+        );
+        eprintln!("skipping the test, clingo not found");
+    }
+    found
+}
+
+/// Runs clingo on the full encoding and the given facts.
+#[cfg(test)]
+fn solve(facts: &[u8], args: &[&str]) -> String {
+    use std::{
+        io::Write,
+        process::{Command, Stdio},
+    };
+    let mut clingo = Command::new("clingo")
+        .args(args)
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("clingo not found");
+    let mut stdin = clingo.stdin.take().unwrap();
+    stdin.write_all(full().as_bytes()).unwrap();
+    stdin.write_all(facts).unwrap();
+    drop(stdin);
+    String::from_utf8(clingo.wait_with_output().unwrap().stdout).unwrap()
+}
+
+/// Solves small models with clingo and compares the number of solutions.
+#[test]
+fn test_models() {
+    if !clingo_found() {
+        return;
+    }
+    let models = [
+        (
+            "var 1..3: x; var 1..3: y; constraint int_lt(x,y); solve satisfy;",
+            3,
+        ),
+        (
+            "array [1..2] of int: a = [1,1]; var 1..3: x; var 1..3: y; \
+             constraint int_lin_eq(a,[x,y],4); solve satisfy;",
+            3,
+        ),
+        (
+            "var 1..2: x; var bool: r; constraint int_eq_reif(x,1,r); solve satisfy;",
+            2,
+        ),
+        (
+            "var 1..3: x; var bool: r; constraint int_le_imp(x,2,r); solve satisfy;",
+            5,
+        ),
+        (
+            "var bool: a; var bool: b; constraint bool_clause([a],[b]); solve satisfy;",
+            3,
+        ),
+        (
+            "var bool: a; var bool: b; var bool: r; constraint bool_xor(a,b,r); solve satisfy;",
+            4,
+        ),
+        (
+            "var bool: a; var 0..1: x; constraint bool2int(a,x); solve satisfy;",
+            2,
+        ),
+        (
+            "var 1..3: i; var 1..9: v; constraint array_int_element(i,[3,6,9],v); solve satisfy;",
+            3,
+        ),
+        (
+            "var -5..5: x; var 1..2: y; var -3..3: z; constraint int_div(x,y,z); solve satisfy;",
+            18,
+        ),
+        (
+            "var 1..5: x; constraint set_in(x,{1,3,5}); solve satisfy;",
+            3,
+        ),
+        (
+            "var set of 1..3: s; constraint set_card(s,2); solve satisfy;",
+            3,
+        ),
+        (
+            "var set of 1..3: s; var set of 1..3: t; constraint set_subset(s,t); solve satisfy;",
+            27,
+        ),
+        (
+            "var set of 1..3: s; var set of 1..3: t; constraint set_union(s,{1},t); solve satisfy;",
+            8,
+        ),
+    ];
+    for (model, expected) in models {
+        let mut facts = vec![];
+        let input = model.replace(';', ";\n");
+        let translator =
+            crate::translate(input.as_bytes(), &mut facts, Default::default()).unwrap();
+        translator.finish().unwrap();
+        let output = solve(&facts, &["0", "--quiet"]);
+        let models = output
+            .lines()
+            .find_map(|l| l.strip_prefix("Models"))
+            .and_then(|l| l.trim_start_matches([' ', ':']).split_whitespace().next())
+            .unwrap_or_default();
+        assert_eq!(models, expected.to_string(), "{model}\n{output}");
+    }
+}
+
+/// The user defined predicates of `tests/corpus/test.fzn` are reported as unsupported.
+#[test]
+fn test_corpus_model() {
+    if !clingo_found() {
+        return;
+    }
+    let fzn = include_bytes!("../tests/corpus/test.fzn");
+    let mut facts = vec![];
+    let translator = crate::translate(&fzn[..], &mut facts, Default::default()).unwrap();
+    translator.finish().unwrap();
+    let output = solve(&facts, &["1"]);
+    assert!(output.lines().any(|l| l == "SATISFIABLE"), "{output}");
+    assert!(output.contains("unsupported(c1,\"bool_01\")"), "{output}");
+}
@@ -0,0 +1,275 @@
+//! A parser for the facts written by the translator.
+//!
+//! Only facts are accepted, terms are integers, strings, constants, function terms and tuples.
+use crate::FlatZincError;
+use anyhow::Result;
+use std::fmt;
+
+/// A ground term.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Term {
+    Int(i128),
+    Str(String),
+    /// A constant or function term, constants have no arguments
+    Func(String, Vec<Term>),
+    Tuple(Vec<Term>),
+}
+impl Term {
+    /// The name of a constant.
+    pub fn constant(&self) -> Option<&str> {
+        match self {
+            Term::Func(name, args) if args.is_empty() => Some(name),
+            _ => None,
+        }
+    }
+    /// The name of an identifier, written as string or as constant.
+    pub fn ident(&self) -> Option<&str> {
+        match self {
+            Term::Str(s) => Some(s),
+            t => t.constant(),
+        }
+    }
+}
+impl fmt::Display for Term {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter, args: &[Term]| {
+            for (i, arg) in args.iter().enumerate() {
+                if i > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{arg}")?;
+            }
+            Ok(())
+        };
+        match self {
+            Term::Int(i) => write!(f, "{i}"),
+            Term::Str(s) => write!(f, "\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+            Term::Func(name, args) if args.is_empty() => write!(f, "{name}"),
+            Term::Func(name, args) => {
+                write!(f, "{name}(")?;
+                join(f, args)?;
+                write!(f, ")")
+            }
+            Term::Tuple(args) => {
+                write!(f, "(")?;
+                join(f, args)?;
+                if args.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// A fact `name(args)` with the line it starts in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fact {
+    pub name: String,
+    pub args: Vec<Term>,
+    pub line: usize,
+}
+
+/// Parses a program consisting of facts and `%` comments.
+pub fn parse_facts(input: &str) -> Result<Vec<Fact>> {
+    let mut parser = Parser {
+        input: input.as_bytes(),
+        pos: 0,
+        line: 1,
+    };
+    let mut facts = vec![];
+    loop {
+        parser.skip();
+        if parser.peek().is_none() {
+            return Ok(facts);
+        }
+        let line = parser.line;
+        let (name, args) = match parser.term()? {
+            Term::Func(name, args) => (name, args),
+            t => return Err(parser.error(format!("expected a predicate, found {t}"))),
+        };
+        parser.expect(b'.')?;
+        facts.push(Fact { name, args, line });
+    }
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    line: usize,
+}
+impl Parser<'_> {
+    fn error(&self, msg: String) -> anyhow::Error {
+        FlatZincError::InvalidFact {
+            line: self.line,
+            msg,
+        }
+        .into()
+    }
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+    fn bump(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == b'\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+    /// Skips whitespace and comments.
+    fn skip(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == b'%' {
+                while self.peek().is_some_and(|c| c != b'\n') {
+                    self.bump();
+                }
+            } else if c.is_ascii_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+    fn expect(&mut self, expected: u8) -> Result<()> {
+        self.skip();
+        match self.bump() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(self.error(format!(
+                "expected '{}', found '{}'",
+                expected as char, c as char
+            ))),
+            None => Err(self.error(format!("expected '{}'", expected as char))),
+        }
+    }
+    fn term(&mut self) -> Result<Term> {
+        self.skip();
+        match self.peek() {
+            Some(b'"') => self.string(),
+            Some(b'(') => {
+                self.bump();
+                let (mut args, trailing_comma) = self.terms(b')')?;
+                if args.len() == 1 && !trailing_comma {
+                    return Ok(args.remove(0));
+                }
+                Ok(Term::Tuple(args))
+            }
+            Some(c) if c == b'-' || c.is_ascii_digit() => self.int(),
+            Some(c) if c == b'_' || c.is_ascii_lowercase() => {
+                let name = self.name();
+                self.skip();
+                if self.peek() == Some(b'(') {
+                    self.bump();
+                    Ok(Term::Func(name, self.terms(b')')?.0))
+                } else {
+                    Ok(Term::Func(name, vec![]))
+                }
+            }
+            Some(c) => Err(self.error(format!("unexpected '{}'", c as char))),
+            None => Err(self.error("unexpected end of input".to_string())),
+        }
+    }
+    /// Comma separated terms up to the closing delimiter and whether there was a trailing comma.
+    fn terms(&mut self, close: u8) -> Result<(Vec<Term>, bool)> {
+        let mut args = vec![];
+        let mut trailing_comma = false;
+        loop {
+            self.skip();
+            if self.peek() == Some(close) {
+                self.bump();
+                return Ok((args, trailing_comma));
+            }
+            args.push(self.term()?);
+            self.skip();
+            match self.peek() {
+                Some(b',') => {
+                    self.bump();
+                    trailing_comma = true;
+                }
+                Some(c) if c == close => trailing_comma = false,
+                _ => {
+                    return Err(self.error(format!("expected ',' or '{}'", close as char)));
+                }
+            }
+        }
+    }
+    fn name(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c == b'_' || c == b'\'' || c.is_ascii_alphanumeric())
+        {
+            self.bump();
+        }
+        String::from_utf8_lossy(&self.input[start..self.pos]).into_owned()
+    }
+    fn int(&mut self) -> Result<Term> {
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.bump();
+        }
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+        }
+        let text = std::str::from_utf8(&self.input[start..self.pos]).unwrap_or_default();
+        text.parse()
+            .map(Term::Int)
+            .map_err(|_| self.error(format!("invalid integer '{text}'")))
+    }
+    fn string(&mut self) -> Result<Term> {
+        self.bump();
+        let mut s = vec![];
+        loop {
+            match self.bump() {
+                Some(b'"') => break,
+                Some(b'\\') => match self.bump() {
+                    Some(b'n') => s.push(b'\n'),
+                    Some(c @ (b'"' | b'\\')) => s.push(c),
+                    _ => return Err(self.error("invalid escape sequence".to_string())),
+                },
+                Some(b'\n') | None => {
+                    return Err(self.error("unterminated string".to_string()));
+                }
+                Some(c) => s.push(c),
+            }
+        }
+        String::from_utf8(s)
+            .map(Term::Str)
+            .map_err(|_| self.error("string is not valid UTF-8".to_string()))
+    }
+}
+
+#[test]
+fn test_parse_facts() {
+    let facts = parse_facts(
+        "% comment\n\
+         variable_type(\"x\",int,range,(value,-1,value,3)).\n\
+         solve(satisfy).  a.\n\
+         s(\"a\\\"b\",(1,)).\n",
+    )
+    .unwrap();
+    let printed: Vec<_> = facts
+        .iter()
+        .map(|f| {
+            (
+                f.line,
+                Term::Func(f.name.clone(), f.args.clone()).to_string(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        printed,
+        [
+            (
+                2,
+                "variable_type(\"x\",int,range,(value,-1,value,3))".to_string()
+            ),
+            (3, "solve(satisfy)".to_string()),
+            (3, "a".to_string()),
+            (4, "s(\"a\\\"b\",(1,))".to_string()),
+        ]
+    );
+    for invalid in ["a(1)", "a(X).", "a(\"b).", "a(1 2).", "a :- b."] {
+        assert!(parse_facts(invalid).is_err(), "{invalid}");
+    }
+}
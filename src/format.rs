@@ -0,0 +1,528 @@
+//! Canonical FlatZinc output of parsed statements.
+//!
+//! Every statement is written on one line with normalized whitespace, floats always have a
+//! fraction or an exponent. [`format`] also sorts the statements into the order required by
+//! FlatZinc, comments stay attached to the statement following them.
+use crate::{input::read_statements, parse_stmt, FlatZincError};
+use anyhow::Result;
+use flatzinc::{
+    AnnExpr, Annotation, ArrayOfBoolExpr, ArrayOfFloatExpr, ArrayOfIntExpr, ArrayOfSetExpr,
+    BasicParType, BasicPredParType, BasicType, BasicVarType, BoolExpr, ConstraintItem, Expr,
+    FloatExpr, Goal, IntExpr, OptimizationType, ParDeclItem, PredIndexSet, PredParType,
+    PredicateItem, SetExpr, SetLiteral, SetLiteralExpr, SolveItem, Stmt, VarDeclItem,
+};
+use std::io::{BufRead, Write};
+
+/// Reads a model and writes it in canonical form.
+pub fn format(input: impl BufRead, mut out: impl Write) -> Result<()> {
+    let mut stmts = vec![];
+    let mut comments = vec![];
+    let mut solve_items = 0;
+    for stmt in read_statements(input) {
+        let (_, stmt) = stmt?;
+        match parse_stmt(&stmt)? {
+            Stmt::Comment(c) if c.trim().is_empty() => {}
+            Stmt::Comment(c) => comments.push(c),
+            stmt => {
+                if matches!(stmt, Stmt::SolveItem(_)) {
+                    solve_items += 1;
+                    if solve_items > 1 {
+                        return Err(FlatZincError::MultipleSolveItems.into());
+                    }
+                }
+                stmts.push((std::mem::take(&mut comments), stmt));
+            }
+        }
+    }
+    stmts.sort_by_key(|(_, stmt)| rank(stmt));
+    for (comments, stmt) in stmts {
+        for c in comments {
+            writeln!(out, "%{c}")?;
+        }
+        writeln!(out, "{}", stmt_to_string(&stmt))?;
+    }
+    for c in comments {
+        writeln!(out, "%{c}")?;
+    }
+    Ok(())
+}
+fn rank(stmt: &Stmt) -> u8 {
+    match stmt {
+        Stmt::Comment(_) | Stmt::Predicate(_) => 0,
+        Stmt::Parameter(_) => 1,
+        Stmt::Variable(_) => 2,
+        Stmt::Constraint(_) => 3,
+        Stmt::SolveItem(_) => 4,
+    }
+}
+
+/// A statement as FlatZinc.
+pub fn stmt_to_string(stmt: &Stmt) -> String {
+    match stmt {
+        Stmt::Comment(c) => format!("%{c}"),
+        Stmt::Predicate(p) => predicate(p),
+        Stmt::Parameter(p) => par_decl_item(p),
+        Stmt::Variable(v) => var_decl_item(v),
+        Stmt::Constraint(c) => constraint(c),
+        Stmt::SolveItem(s) => solve_item(s),
+    }
+}
+fn predicate(p: &PredicateItem) -> String {
+    let params: Vec<_> = p
+        .parameters
+        .iter()
+        .map(|(t, id)| match t {
+            PredParType::Basic(t) => format!("{}: {id}", pred_par_type(t)),
+            PredParType::Array { ix, par_type } => {
+                let ix = match ix {
+                    PredIndexSet::IndexSet(n) => format!("1..{n}"),
+                    PredIndexSet::Int => "int".to_string(),
+                };
+                format!("array [{ix}] of {}: {id}", pred_par_type(par_type))
+            }
+        })
+        .collect();
+    format!("predicate {}({});", p.id, params.join(", "))
+}
+fn pred_par_type(t: &BasicPredParType) -> String {
+    match t {
+        BasicPredParType::BasicParType(BasicParType::BasicType(t)) => basic_type(t).to_string(),
+        BasicPredParType::BasicParType(BasicParType::SetOfInt) => "set of int".to_string(),
+        BasicPredParType::BasicVarType(t) => format!("var {}", var_type(t)),
+        BasicPredParType::VarSetOfInt => "var set of int".to_string(),
+        BasicPredParType::IntInRange(lb, ub) => format!("{lb}..{ub}"),
+        BasicPredParType::IntInSet(set) => int_set(set),
+        BasicPredParType::BoundedFloat(lb, ub) => format!("{}..{}", float(*lb), float(*ub)),
+        BasicPredParType::FloatInSet(set) => list("{", set.iter().map(|f| float(*f)), "}"),
+        BasicPredParType::SubSetOfIntSet(set) => format!("set of {}", int_set(set)),
+        BasicPredParType::SubSetOfIntRange(lb, ub) => format!("set of {lb}..{ub}"),
+    }
+}
+fn var_type(t: &BasicVarType) -> String {
+    match t {
+        BasicVarType::BasicType(t) => basic_type(t).to_string(),
+        BasicVarType::IntInRange(lb, ub) => format!("{lb}..{ub}"),
+        BasicVarType::IntInSet(set) => int_set(set),
+        BasicVarType::BoundedFloat(lb, ub) => format!("{}..{}", float(*lb), float(*ub)),
+        BasicVarType::SubSetOfIntSet(set) => format!("set of {}", int_set(set)),
+        BasicVarType::SubSetOfIntRange(lb, ub) => format!("set of {lb}..{ub}"),
+    }
+}
+fn basic_type(t: &BasicType) -> &'static str {
+    match t {
+        BasicType::Bool => "bool",
+        BasicType::Int => "int",
+        BasicType::Float => "float",
+    }
+}
+fn par_decl_item(p: &ParDeclItem) -> String {
+    let (t, id, value) = match p {
+        ParDeclItem::Bool { id, bool } => ("bool".to_string(), id, bool.to_string()),
+        ParDeclItem::Int { id, int } => ("int".to_string(), id, int.to_string()),
+        ParDeclItem::Float { id, float: f } => ("float".to_string(), id, float(*f)),
+        ParDeclItem::SetOfInt { id, set_literal } => (
+            "set of int".to_string(),
+            id,
+            set_literal_to_string(set_literal),
+        ),
+        ParDeclItem::ArrayOfBool { ix, id, v } => (
+            format!("array [1..{}] of bool", ix.0),
+            id,
+            list("[", v.iter().map(bool::to_string), "]"),
+        ),
+        ParDeclItem::ArrayOfInt { ix, id, v } => (
+            format!("array [1..{}] of int", ix.0),
+            id,
+            list("[", v.iter().map(i128::to_string), "]"),
+        ),
+        ParDeclItem::ArrayOfFloat { ix, id, v } => (
+            format!("array [1..{}] of float", ix.0),
+            id,
+            list("[", v.iter().map(|f| float(*f)), "]"),
+        ),
+        ParDeclItem::ArrayOfSet { ix, id, v } => (
+            format!("array [1..{}] of set of int", ix.0),
+            id,
+            list("[", v.iter().map(set_literal_to_string), "]"),
+        ),
+    };
+    format!("{t}: {id} = {value};")
+}
+fn var_decl_item(v: &VarDeclItem) -> String {
+    let array = |ix: &flatzinc::IndexSet, t: String| format!("array [1..{}] of var {t}", ix.0);
+    let (t, id, annos, value) = match v {
+        VarDeclItem::Bool { id, expr, annos } => {
+            ("bool".to_string(), id, annos, expr.as_ref().map(bool_expr))
+        }
+        VarDeclItem::Int { id, expr, annos } => {
+            ("int".to_string(), id, annos, expr.as_ref().map(int_expr))
+        }
+        VarDeclItem::IntInRange {
+            id,
+            lb,
+            ub,
+            expr,
+            annos,
+        } => (
+            format!("{lb}..{ub}"),
+            id,
+            annos,
+            expr.as_ref().map(int_expr),
+        ),
+        VarDeclItem::IntInSet {
+            id,
+            set,
+            expr,
+            annos,
+        } => (int_set(set), id, annos, expr.as_ref().map(int_expr)),
+        VarDeclItem::Float { id, expr, annos } => (
+            "float".to_string(),
+            id,
+            annos,
+            expr.as_ref().map(float_expr),
+        ),
+        VarDeclItem::BoundedFloat {
+            id,
+            lb,
+            ub,
+            expr,
+            annos,
+        } => (
+            format!("{}..{}", float(*lb), float(*ub)),
+            id,
+            annos,
+            expr.as_ref().map(float_expr),
+        ),
+        VarDeclItem::SetOfInt { id, expr, annos } => (
+            "set of int".to_string(),
+            id,
+            annos,
+            expr.as_ref().map(set_expr),
+        ),
+        VarDeclItem::SubSetOfIntSet {
+            id,
+            set,
+            expr,
+            annos,
+        } => (
+            format!("set of {}", int_set(set)),
+            id,
+            annos,
+            expr.as_ref().map(set_expr),
+        ),
+        VarDeclItem::SubSetOfIntRange {
+            id,
+            lb,
+            ub,
+            expr,
+            annos,
+        } => (
+            format!("set of {lb}..{ub}"),
+            id,
+            annos,
+            expr.as_ref().map(set_expr),
+        ),
+        VarDeclItem::ArrayOfBool {
+            ix,
+            id,
+            annos,
+            array_expr,
+        } => (
+            array(ix, "bool".to_string()),
+            id,
+            annos,
+            array_expr.as_ref().map(|e| match e {
+                ArrayOfBoolExpr::Array(v) => list("[", v.iter().map(bool_expr), "]"),
+                ArrayOfBoolExpr::VarParIdentifier(id) => id.clone(),
+            }),
+        ),
+        VarDeclItem::ArrayOfInt {
+            ix,
+            id,
+            annos,
+            array_expr,
+        } => (
+            array(ix, "int".to_string()),
+            id,
+            annos,
+            array_expr.as_ref().map(array_of_int_expr),
+        ),
+        VarDeclItem::ArrayOfIntInRange {
+            lb,
+            ub,
+            ix,
+            id,
+            annos,
+            array_expr,
+        } => (
+            array(ix, format!("{lb}..{ub}")),
+            id,
+            annos,
+            array_expr.as_ref().map(array_of_int_expr),
+        ),
+        VarDeclItem::ArrayOfIntInSet {
+            set,
+            ix,
+            id,
+            annos,
+            array_expr,
+        } => (
+            array(ix, int_set(set)),
+            id,
+            annos,
+            array_expr.as_ref().map(array_of_int_expr),
+        ),
+        VarDeclItem::ArrayOfFloat {
+            ix,
+            id,
+            annos,
+            array_expr,
+        } => (
+            array(ix, "float".to_string()),
+            id,
+            annos,
+            array_expr.as_ref().map(array_of_float_expr),
+        ),
+        VarDeclItem::ArrayOfBoundedFloat {
+            lb,
+            ub,
+            ix,
+            id,
+            annos,
+            array_expr,
+        } => (
+            array(ix, format!("{}..{}", float(*lb), float(*ub))),
+            id,
+            annos,
+            array_expr.as_ref().map(array_of_float_expr),
+        ),
+        VarDeclItem::ArrayOfSet {
+            ix,
+            id,
+            annos,
+            array_expr,
+        } => (
+            array(ix, "set of int".to_string()),
+            id,
+            annos,
+            array_expr.as_ref().map(array_of_set_expr),
+        ),
+        VarDeclItem::ArrayOfSubSetOfIntRange {
+            ub,
+            lb,
+            ix,
+            id,
+            annos,
+            array_expr,
+        } => (
+            array(ix, format!("set of {lb}..{ub}")),
+            id,
+            annos,
+            array_expr.as_ref().map(array_of_set_expr),
+        ),
+        VarDeclItem::ArrayOfSubSetOfIntSet {
+            set,
+            ix,
+            id,
+            annos,
+            array_expr,
+        } => (
+            array(ix, format!("set of {}", int_set(set))),
+            id,
+            annos,
+            array_expr.as_ref().map(array_of_set_expr),
+        ),
+    };
+    let t = if t.starts_with("array") {
+        t
+    } else {
+        format!("var {t}")
+    };
+    match value {
+        Some(value) => format!("{t}: {id}{} = {value};", annotations(annos)),
+        None => format!("{t}: {id}{};", annotations(annos)),
+    }
+}
+fn constraint(c: &ConstraintItem) -> String {
+    format!(
+        "constraint {}{}{};",
+        c.id,
+        list("(", c.exprs.iter().map(expr), ")"),
+        annotations(&c.annos)
+    )
+}
+fn solve_item(s: &SolveItem) -> String {
+    let opt = |ot: &OptimizationType| match ot {
+        OptimizationType::Minimize => "minimize",
+        OptimizationType::Maximize => "maximize",
+    };
+    let goal = match &s.goal {
+        Goal::Satisfy => "satisfy".to_string(),
+        Goal::OptimizeBool(ot, e) => format!("{} {}", opt(ot), bool_expr(e)),
+        Goal::OptimizeInt(ot, e) => format!("{} {}", opt(ot), int_expr(e)),
+        Goal::OptimizeFloat(ot, e) => format!("{} {}", opt(ot), float_expr(e)),
+        Goal::OptimizeSet(ot, e) => format!("{} {}", opt(ot), set_expr(e)),
+    };
+    format!("solve{} {goal};", annotations(&s.annotations))
+}
+fn annotations(annos: &[Annotation]) -> String {
+    annos
+        .iter()
+        .map(|a| format!(" :: {}", annotation(a)))
+        .collect()
+}
+fn annotation(a: &Annotation) -> String {
+    if a.expressions.is_empty() {
+        return a.id.clone();
+    }
+    let exprs = a.expressions.iter().map(|e| match e {
+        AnnExpr::Annotations(v) => list("[", v.iter().map(annotation), "]"),
+        AnnExpr::String(s) => format!("{s:?}"),
+        AnnExpr::Expr(e) => expr(e),
+    });
+    format!("{}{}", a.id, list("(", exprs, ")"))
+}
+fn expr(e: &Expr) -> String {
+    match e {
+        Expr::VarParIdentifier(id) => id.clone(),
+        Expr::Bool(b) => b.to_string(),
+        Expr::Int(i) => i.to_string(),
+        Expr::Float(f) => float(*f),
+        Expr::Set(s) => set_literal_expr(s),
+        Expr::ArrayOfBool(v) => list("[", v.iter().map(bool_expr), "]"),
+        Expr::ArrayOfInt(v) => list("[", v.iter().map(int_expr), "]"),
+        Expr::ArrayOfFloat(v) => list("[", v.iter().map(float_expr), "]"),
+        Expr::ArrayOfSet(v) => list("[", v.iter().map(set_expr), "]"),
+    }
+}
+fn array_of_int_expr(e: &ArrayOfIntExpr) -> String {
+    match e {
+        ArrayOfIntExpr::Array(v) => list("[", v.iter().map(int_expr), "]"),
+        ArrayOfIntExpr::VarParIdentifier(id) => id.clone(),
+    }
+}
+fn array_of_float_expr(e: &ArrayOfFloatExpr) -> String {
+    match e {
+        ArrayOfFloatExpr::Array(v) => list("[", v.iter().map(float_expr), "]"),
+        ArrayOfFloatExpr::VarParIdentifier(id) => id.clone(),
+    }
+}
+fn array_of_set_expr(e: &ArrayOfSetExpr) -> String {
+    match e {
+        ArrayOfSetExpr::Array(v) => list("[", v.iter().map(set_expr), "]"),
+        ArrayOfSetExpr::VarParIdentifier(id) => id.clone(),
+    }
+}
+fn bool_expr(e: &BoolExpr) -> String {
+    match e {
+        BoolExpr::Bool(b) => b.to_string(),
+        BoolExpr::VarParIdentifier(id) => id.clone(),
+    }
+}
+fn int_expr(e: &IntExpr) -> String {
+    match e {
+        IntExpr::Int(i) => i.to_string(),
+        IntExpr::VarParIdentifier(id) => id.clone(),
+    }
+}
+fn float_expr(e: &FloatExpr) -> String {
+    match e {
+        FloatExpr::Float(f) => float(*f),
+        FloatExpr::VarParIdentifier(id) => id.clone(),
+    }
+}
+pub(crate) fn set_expr(e: &SetExpr) -> String {
+    match e {
+        SetExpr::Set(s) => set_literal_expr(s),
+        SetExpr::VarParIdentifier(id) => id.clone(),
+    }
+}
+fn set_literal_expr(s: &SetLiteralExpr) -> String {
+    match s {
+        SetLiteralExpr::IntInRange(lb, ub) => format!("{}..{}", int_expr(lb), int_expr(ub)),
+        SetLiteralExpr::BoundedFloat(lb, ub) => {
+            format!("{}..{}", float_expr(lb), float_expr(ub))
+        }
+        SetLiteralExpr::SetInts(v) => list("{", v.iter().map(int_expr), "}"),
+        SetLiteralExpr::SetFloats(v) => list("{", v.iter().map(float_expr), "}"),
+    }
+}
+fn set_literal_to_string(s: &SetLiteral) -> String {
+    match s {
+        SetLiteral::IntRange(lb, ub) => format!("{lb}..{ub}"),
+        SetLiteral::BoundedFloat(lb, ub) => format!("{}..{}", float(*lb), float(*ub)),
+        SetLiteral::SetInts(v) => int_set(v),
+        SetLiteral::SetFloats(v) => list("{", v.iter().map(|f| float(*f)), "}"),
+    }
+}
+pub(crate) fn int_set(set: &[i128]) -> String {
+    list("{", set.iter().map(i128::to_string), "}")
+}
+/// A float literal, unlike `Display` the debug output keeps the fraction of whole numbers.
+fn float(f: f64) -> String {
+    format!("{f:?}")
+}
+/// Sets are written without spaces, all other lists with a space after each comma.
+fn list(open: &str, items: impl Iterator<Item = String>, close: &str) -> String {
+    let separator = if open == "{" { "," } else { ", " };
+    format!("{open}{}{close}", items.collect::<Vec<_>>().join(separator))
+}
+
+#[test]
+fn test_format() {
+    let input = "% model\n\
+                 var   1..3 : x::output_var;\n\
+                 \n\
+                 predicate p(array [int] of var int: a,var set of int:s, {1.5,2.0}: f);\n\
+                 constraint int_lin_le( [1,-2] ,[x, y],k )::domain;\n\
+                 % parameters\n\
+                 int : k=2;\n\
+                 array [1..2] of set of int: ss = [1..2,{}];\n\
+                 var 0.5..2.0: z;\n\
+                 array [1..2] of var int: y:: output_array([1..2]) = [x,3];\n\
+                 solve :: int_search([x],input_order,indomain_min,complete) minimize x;\n\
+                 % end\n";
+    let mut out = vec![];
+    format(input.as_bytes(), &mut out).unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "predicate p(array [int] of var int: a, var set of int: s, {1.5,2.0}: f);\n\
+         % parameters\n\
+         int: k = 2;\n\
+         array [1..2] of set of int: ss = [1..2, {}];\n\
+         % model\n\
+         var 1..3: x :: output_var;\n\
+         var 0.5..2.0: z;\n\
+         array [1..2] of var int: y :: output_array([1..2]) = [x, 3];\n\
+         constraint int_lin_le([1, -2], [x, y], k) :: domain;\n\
+         solve :: int_search([x], input_order, indomain_min, complete) minimize x;\n\
+         % end\n"
+    );
+    let mut out = vec![];
+    assert!(format("solve satisfy;\nsolve satisfy;\n".as_bytes(), &mut out).is_err());
+    let mut out = vec![];
+    format(
+        "var 1..3: x; var 1..3: y;\nconstraint int_lt(x,\n  y);\nsolve\nsatisfy;\n".as_bytes(),
+        &mut out,
+    )
+    .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "var 1..3: x;\nvar 1..3: y;\nconstraint int_lt(x, y);\nsolve satisfy;\n"
+    );
+
+    let facts = |fzn: &[u8]| {
+        let mut lp = vec![];
+        crate::translate(fzn, &mut lp, crate::Options::default()).unwrap();
+        String::from_utf8(lp)
+            .unwrap()
+            .lines()
+            .filter(|l| !l.starts_with('%'))
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    let model = include_str!("../tests/corpus/test.fzn");
+    let mut formatted = vec![];
+    format(model.as_bytes(), &mut formatted).unwrap();
+    assert_eq!(facts(&formatted), facts(model.as_bytes()));
+}
@@ -0,0 +1,313 @@
+//! Input of the translation, memory-mapped where possible.
+//!
+//! Uncompressed files are mapped into memory and split into statements in place, so no line is
+//! copied before parsing. Compressed files and stdin are decoded and read line by line, and
+//! split into the same statements.
+use crate::{compression, translate, translate_str, Options, Translator};
+use anyhow::Result;
+use memmap2::Mmap;
+use std::{
+    fs::File,
+    io::{BufRead, Write},
+    path::Path,
+};
+
+/// A FlatZinc model to translate.
+pub enum Input {
+    Mapped(Mmap),
+    Stream(Box<dyn BufRead>),
+}
+impl Input {
+    /// Opens a file, mapping it into memory unless it is compressed or cannot be mapped.
+    pub fn open(path: &Path) -> Result<Input> {
+        let file = File::open(path)?;
+        // SAFETY: like any input, the file must not be modified during the translation.
+        if let Ok(map) = unsafe { Mmap::map(&file) } {
+            if !compression::is_compressed(path, &map) {
+                return Ok(Input::Mapped(map));
+            }
+        }
+        Ok(Input::Stream(compression::open_input(path)?))
+    }
+    /// Reads stdin, decompressing it if necessary.
+    pub fn stdin() -> Result<Input> {
+        Ok(Input::Stream(compression::stdin()?))
+    }
+    /// Translates the model, see [`translate`] and [`translate_str`].
+    pub fn translate(self, out: impl Write, options: Options) -> Result<Translator> {
+        match self {
+            Input::Mapped(map) => translate_str(std::str::from_utf8(&map)?, out, options),
+            Input::Stream(reader) => translate(reader, out, options),
+        }
+    }
+}
+
+/// Splits a model into statements with the line they start in.
+///
+/// A statement ends with a `;` outside of strings and comments, together with the rest of its
+/// line if that is only a comment. Comments and empty lines outside of statements are
+/// statements of their own, like lines in FlatZinc with one statement per line.
+pub fn statements(text: &str) -> Statements<'_> {
+    Statements {
+        text,
+        pos: 0,
+        line: 1,
+    }
+}
+pub struct Statements<'a> {
+    text: &'a str,
+    pos: usize,
+    line: usize,
+}
+impl<'a> Iterator for Statements<'a> {
+    type Item = (usize, &'a str);
+    fn next(&mut self) -> Option<(usize, &'a str)> {
+        let start = self.pos;
+        if start >= self.text.len() {
+            return None;
+        }
+        let (end, next, _) = split(self.text.as_bytes(), start);
+        let line = self.line;
+        self.line += self.text.as_bytes()[start..next]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count();
+        self.pos = next;
+        let stmt = &self.text[start..end];
+        Some((line, stmt.strip_suffix('\r').unwrap_or(stmt)))
+    }
+}
+/// Finds the statement starting at `start`.
+///
+/// Returns its end, the start of the next statement and whether the statement is complete,
+/// that is not cut off by the end of the text.
+fn split(bytes: &[u8], start: usize) -> (usize, usize, bool) {
+    let mut scanner = Scanner::new(start);
+    match scanner.scan(bytes) {
+        Some((end, next)) => (end, next, true),
+        None => (bytes.len(), bytes.len(), scanner.complete()),
+    }
+}
+
+/// Search for the end of a statement, which can be resumed when more text is appended.
+#[derive(Clone, Copy)]
+struct Scanner {
+    /// Next byte to look at
+    pos: usize,
+    /// Inside of a statement
+    open: bool,
+    /// End of the last statement in the piece
+    done: Option<usize>,
+    string: bool,
+    comment: bool,
+}
+impl Scanner {
+    fn new(start: usize) -> Scanner {
+        Scanner {
+            pos: start,
+            open: false,
+            done: None,
+            string: false,
+            comment: false,
+        }
+    }
+    /// Scans the text from the last position, returns the end of the statement and the start of
+    /// the next one once they are found.
+    fn scan(&mut self, bytes: &[u8]) -> Option<(usize, usize)> {
+        let Scanner {
+            pos: mut i,
+            mut open,
+            mut done,
+            mut string,
+            mut comment,
+        } = *self;
+        let found = loop {
+            if i >= bytes.len() {
+                break None;
+            }
+            let c = bytes[i];
+            if string {
+                match c {
+                    b'\\' => i += 1,
+                    b'"' => string = false,
+                    _ => {}
+                }
+                i += 1;
+                continue;
+            }
+            if comment {
+                if c != b'\n' {
+                    i += 1;
+                    continue;
+                }
+                comment = false;
+            }
+            match c {
+                b'\n' if !open => break Some((i, i + 1)),
+                b'%' => comment = true,
+                b';' => {
+                    open = false;
+                    done = Some(i + 1);
+                }
+                c if c.is_ascii_whitespace() => {}
+                c => {
+                    if !open {
+                        if let Some(end) = done {
+                            break Some((end, end));
+                        }
+                        open = true;
+                    }
+                    string = c == b'"';
+                }
+            }
+            i += 1;
+        };
+        *self = Scanner {
+            pos: i,
+            open,
+            done,
+            string,
+            comment,
+        };
+        found
+    }
+    /// Whether the statement is complete if the text ends here.
+    fn complete(&self) -> bool {
+        !self.open && !self.string
+    }
+    /// Adjusts the positions after the first `n` bytes of the text were removed.
+    fn shift(&mut self, n: usize) {
+        self.pos -= n;
+        self.done = self.done.map(|end| end - n);
+    }
+}
+
+/// Splits a stream into statements like [`statements`], reading one line at a time.
+///
+/// The scan of a statement spanning several lines resumes where the last line ended.
+pub fn read_statements<R: BufRead>(input: R) -> ReadStatements<R> {
+    ReadStatements {
+        input,
+        buf: String::new(),
+        start: 0,
+        scanner: Scanner::new(0),
+        line: 1,
+    }
+}
+pub struct ReadStatements<R> {
+    input: R,
+    /// Lines read, the statements before `start` are returned already
+    buf: String,
+    start: usize,
+    scanner: Scanner,
+    line: usize,
+}
+impl<R: BufRead> ReadStatements<R> {
+    /// Returns the statement ending at `end`, the next one starts at `next`.
+    fn take(&mut self, end: usize, next: usize) -> (usize, String) {
+        let line = self.line;
+        self.line += self.buf.as_bytes()[self.start..next]
+            .iter()
+            .filter(|&&b| b == b'\n')
+            .count();
+        let mut stmt = if self.start == 0 && next == self.buf.len() {
+            // the usual case of one statement in the buffer, which is moved out
+            let mut stmt = std::mem::take(&mut self.buf);
+            stmt.truncate(end);
+            stmt
+        } else {
+            self.buf[self.start..end].to_string()
+        };
+        if stmt.ends_with('\r') {
+            stmt.pop();
+        }
+        self.start = if self.buf.is_empty() { 0 } else { next };
+        self.scanner = Scanner::new(self.start);
+        (line, stmt)
+    }
+}
+impl<R: BufRead> Iterator for ReadStatements<R> {
+    type Item = Result<(usize, String)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((end, next)) = self.scanner.scan(self.buf.as_bytes()) {
+                return Some(Ok(self.take(end, next)));
+            }
+            // only the start of a statement is left, which is moved to the front once
+            if self.start > 0 {
+                self.buf.drain(..self.start);
+                self.scanner.shift(self.start);
+                self.start = 0;
+            }
+            match self.input.read_line(&mut self.buf) {
+                Ok(0) if self.buf.is_empty() => return None,
+                Ok(0) => {
+                    let len = self.buf.len();
+                    return Some(Ok(self.take(len, len)));
+                }
+                Ok(_) => {}
+                Err(err) => return Some(Err(err.into())),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_statements() {
+    let text = "var 1..3: x;\r\n\
+                constraint int_le(\n  x, % first\n  2);\n\
+                var 1..3: y; var 1..3: z; % both\n\
+                \n\
+                % comment\n\
+                constraint p(x) :: a(\"b;\\\"c\"); solve satisfy;";
+    let stmts: Vec<_> = statements(text).collect();
+    assert_eq!(
+        stmts,
+        [
+            (1, "var 1..3: x;"),
+            (2, "constraint int_le(\n  x, % first\n  2);"),
+            (5, "var 1..3: y;"),
+            (5, " var 1..3: z; % both"),
+            (6, ""),
+            (7, "% comment"),
+            (8, "constraint p(x) :: a(\"b;\\\"c\");"),
+            (8, " solve satisfy;"),
+        ]
+    );
+    assert_eq!(statements("").count(), 0);
+    assert_eq!(statements("a;\n").collect::<Vec<_>>(), [(1, "a;")]);
+
+    let model = include_str!("../tests/corpus/test.fzn");
+    let stream = |text: &str| {
+        read_statements(text.as_bytes())
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+    };
+    let owned = |stmts: Statements| {
+        stmts
+            .map(|(line, stmt)| (line, stmt.to_string()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(stream(text), owned(statements(text)));
+    assert_eq!(stream(model), owned(statements(model)));
+    // a statement over many lines, continued by escapes and comments
+    let mut long = "a; array [1..50000] of int: b = [\n".to_string();
+    for i in 0..50000 {
+        long.push_str(&format!("{i}, % \"\n"));
+    }
+    long.push_str("0] :: c(\"d\\\n;\"); e;\r\nf");
+    assert_eq!(stream(&long), owned(statements(&long)));
+    assert_eq!(stream(&long).len(), 4);
+
+    let translate = |mapped: bool| {
+        let mut res = Vec::new();
+        let translator = if mapped {
+            translate_str(model, &mut res, Options::default())
+        } else {
+            translate(model.as_bytes(), &mut res, Options::default())
+        };
+        translator.unwrap().finish().unwrap();
+        String::from_utf8(res).unwrap()
+    };
+    assert_eq!(translate(true), translate(false));
+}
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use clap::ValueEnum;
 use flatzinc::{
     AnnExpr, Annotation, ArrayOfBoolExpr, ArrayOfFloatExpr, ArrayOfIntExpr, ArrayOfSetExpr,
     BasicParType, BasicPredParType, BasicType, BasicVarType, BoolExpr, ConstraintItem, Expr,
@@ -6,9 +7,33 @@ use flatzinc::{
     PredicateItem, SetExpr, SetLiteral, SetLiteralExpr, SolveItem, Stmt, VarDeclItem,
 };
 use log::warn;
-use std::io::Write;
+use std::fmt::Display;
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+};
+use term::{
+    bool_expr, bool_literal, dec_set_expr, dec_set_literal, dec_set_literal_expr, float_expr,
+    ident, int_bound, int_expr, int_literal, set_type, string_term, Ident, Scalar,
+};
 use thiserror::Error;
 
+pub mod alias;
+pub mod batch;
+mod clingcon;
+pub mod compression;
+mod dl;
+mod domain;
+pub mod encoding;
+pub mod facts;
+pub mod format;
+pub mod input;
+mod linear;
+pub mod parallel;
+pub mod presolve;
+pub mod reverse;
+mod term;
+
 #[derive(Error, Debug)]
 pub enum FlatZincError {
     #[error("More than one solve item")]
@@ -17,6 +42,16 @@ pub enum FlatZincError {
     NoSolveItem,
     #[error("ParseError:\n{msg}")]
     ParseError { msg: String },
+    #[error("Integer {value} in {id} is outside of clingo's 32-bit range")]
+    IntOutOfRange { id: String, value: i128 },
+    #[error("Statements in wrong order: {kind} in line {line}")]
+    WrongOrder { kind: &'static str, line: usize },
+    #[error("Invalid output_array annotation of {id}: {msg}")]
+    InvalidOutputArray { id: String, msg: String },
+    #[error("Invalid fact in line {line}: {msg}")]
+    InvalidFact { line: usize, msg: String },
+    #[error("Cannot reconstruct {id} from the facts: {msg}")]
+    InvalidFacts { id: String, msg: String },
 }
 
 #[test]
@@ -104,7 +139,7 @@ fn test_variables() {
     write_fz_stmt(&mut res, "var 0.5..1.5: b = 1.0;", &mut counter, &mut level).unwrap();
     assert_eq!(
         std::str::from_utf8(&res).unwrap(),
-        "variable_type(\"b\",float,(bounds,value,\"0.5\",value,\"1.5\")).\n\
+        "variable_type(\"b\",float,bounds,(value,\"0.5\",value,\"1.5\")).\n\
          variable_value(\"b\",value,\"1\").\n"
             .to_string()
     );
@@ -134,7 +169,7 @@ fn test_variables() {
     let mut res = Vec::new();
     write_fz_stmt(
         &mut res,
-        "array [1..2] of var float : e :: output_array([1..2, 1..2]) = [42.1,23.1];",
+        "array [1..2] of var float : e :: output_array([1..2, 1..1]) = [42.1,23.1];",
         &mut counter,
         &mut level,
     )
@@ -145,7 +180,9 @@ fn test_variables() {
          variable_value(\"e\",array,(0,value,\"42.1\")).\n\
          variable_value(\"e\",array,(1,value,\"23.1\")).\n\
          output_array(\"e\",0,(1,2)).\n\
-         output_array(\"e\",1,(1,2)).\n"
+         output_array(\"e\",1,(1,1)).\n\
+         output_element(\"e\",0,value,\"42.1\").\n\
+         output_element(\"e\",1,value,\"23.1\").\n"
             .to_string()
     );
     let mut res = Vec::new();
@@ -180,22 +217,18 @@ fn test_variables() {
          variable_value(\"f\",set,(value,23)).\n"
             .to_string()
     );
-    // let mut res = Vec::new(); // TODO: Check if set of floats are allowed
-    // write_fz_stmt(
-    //     &mut res,
-    //     "var set of float: g = {23.1,42.1};",
-    //     &mut counter,
-    //     &mut level,
-    // )
-    // .unwrap();
-    // assert_eq!(
-    //     std::str::from_utf8(&res).unwrap(),
-    //     "variable_value(\"g\",set,(value,\"23.1\")).\nvariable_value(\"g\",set,(value,\"42.1\")).\n".to_string()
-    // );
+    // FlatZinc has no set of float variables
+    assert!(write_fz_stmt(
+        Vec::new(),
+        "var set of float: g = {23.1,42.1};",
+        &mut counter,
+        &mut level,
+    )
+    .is_err());
     let mut res = Vec::new();
     write_fz_stmt(
         &mut res,
-        "array [1..3] of var set of 17..42: h = [{42,17},23..X,{}];", //TODO: check empty set
+        "array [1..3] of var set of 17..42: h = [{42,17},23..X,{}];",
         &mut counter,
         &mut level,
     )
@@ -218,19 +251,19 @@ fn test_parameters() {
     write_fz_stmt(&mut res, "int : a = 1;", &mut counter, &mut level).unwrap();
     assert_eq!(
         std::str::from_utf8(&res).unwrap(),
-        "parameter_value(\"a\",value,1).\n".to_string()
+        "parameter_type(\"a\",int).\nparameter_value(\"a\",value,1).\n".to_string()
     );
     let mut res = Vec::new();
     write_fz_stmt(&mut res, "float : b = 1.1;", &mut counter, &mut level).unwrap();
     assert_eq!(
         std::str::from_utf8(&res).unwrap(),
-        "parameter_value(\"b\",value,\"1.1\").\n".to_string()
+        "parameter_type(\"b\",float).\nparameter_value(\"b\",value,\"1.1\").\n".to_string()
     );
     let mut res = Vec::new();
     write_fz_stmt(&mut res, "bool : c = true;", &mut counter, &mut level).unwrap();
     assert_eq!(
         std::str::from_utf8(&res).unwrap(),
-        "parameter_value(\"c\",value,true).\n".to_string()
+        "parameter_type(\"c\",bool).\nparameter_value(\"c\",value,true).\n".to_string()
     );
     let mut res = Vec::new();
     write_fz_stmt(
@@ -242,7 +275,8 @@ fn test_parameters() {
     .unwrap();
     assert_eq!(
         std::str::from_utf8(&res).unwrap(),
-        "parameter_value(\"d\",array,(0,value,42)).\n\
+        "parameter_type(\"d\",array(2,int)).\n\
+         parameter_value(\"d\",array,(0,value,42)).\n\
          parameter_value(\"d\",array,(1,value,23)).\n"
             .to_string()
     );
@@ -256,7 +290,8 @@ fn test_parameters() {
     .unwrap();
     assert_eq!(
         std::str::from_utf8(&res).unwrap(),
-        "parameter_value(\"e\",array,(0,value,\"42.1\")).\n\
+        "parameter_type(\"e\",array(2,float)).\n\
+         parameter_value(\"e\",array,(0,value,\"42.1\")).\n\
          parameter_value(\"e\",array,(1,value,\"23\")).\n"
             .to_string()
     );
@@ -270,20 +305,18 @@ fn test_parameters() {
     .unwrap();
     assert_eq!(
         std::str::from_utf8(&res).unwrap(),
-        "parameter_value(\"f\",range,(value,23,value,42)).\n".to_string()
+        "parameter_type(\"f\",set_of_int).\n\
+         parameter_value(\"f\",range,(value,23,value,42)).\n"
+            .to_string()
     );
-    // let mut res = Vec::new(); // TODO: check if/where set of floats are allowed
-    // write_fz_stmt(
-    //     &mut res,
-    //     "set of float : g = {42.1,23.0};",
-    //     &mut counter,
-    //     &mut level,
-    // )
-    // .unwrap();
-    // assert_eq!(
-    //     std::str::from_utf8(&res).unwrap(),
-    //     "parameter_value(\"g\",set,(value,\"23\"))).\nparameter_value(\"g\",set,(value,\"42.1\"))).\n".to_string()
-    // );
+    // sets of floats only occur as domains and in constraints
+    assert!(write_fz_stmt(
+        Vec::new(),
+        "set of float : g = {42.1,23.0};",
+        &mut counter,
+        &mut level,
+    )
+    .is_err());
     let mut res = Vec::new();
     write_fz_stmt(
         &mut res,
@@ -294,12 +327,55 @@ fn test_parameters() {
     .unwrap();
     assert_eq!(
         std::str::from_utf8(&res).unwrap(),
-        "parameter_value(\"h\",array,(0,set,(value,42))).\n\
+        "parameter_type(\"h\",array(3,set_of_int)).\n\
+         parameter_value(\"h\",array,(0,set,(value,42))).\n\
          parameter_value(\"h\",array,(0,set,(value,17))).\n\
          parameter_value(\"h\",array,(1,range,(value,1,value,5))).\n\
          parameter_value(\"h\",array,(2,empty_set)).\n"
             .to_string()
     );
+
+    let mut translator = Translator::new(Options::default());
+    for stmt in [
+        "bool: b = true;",
+        "float: f = 1.5;",
+        "int: i = 1;",
+        "set of int: s = 1..2;",
+        "array [1..2] of bool: a = [true,false];",
+    ] {
+        translator.write_fz_stmt(std::io::sink(), stmt).unwrap();
+    }
+    let mut kept: Vec<_> = translator.params.keys().map(String::as_str).collect();
+    kept.sort();
+    assert_eq!(kept, ["a", "i", "s"]);
+}
+#[test]
+fn test_float_sets() {
+    let mut translator = Translator::default();
+    let mut res = Vec::new();
+    for stmt in [
+        "predicate p({1.5,2.5}: s, array [int] of 0.5..1.5: b);",
+        "var 0.5..1.5: x;",
+        "array [1..2] of var 0.5..1.5: y;",
+        "constraint p({1.5,x},[0.5..1.5,{},{2.5}]);",
+    ] {
+        translator.write_fz_stmt(&mut res, stmt).unwrap();
+    }
+    assert_eq!(
+        std::str::from_utf8(&res).unwrap(),
+        "predicate(\"p\").\n\
+         predicate_parameter(\"p\",0,\"s\",float,set,(value,\"1.5\")).\n\
+         predicate_parameter(\"p\",0,\"s\",float,set,(value,\"2.5\")).\n\
+         predicate_parameter(\"p\",1,\"b\",array(int,float,bounds,(value,\"0.5\",value,\"1.5\"))).\n\
+         variable_type(\"x\",float,bounds,(value,\"0.5\",value,\"1.5\")).\n\
+         variable_type(\"y\",array(2,float,bounds,(value,\"0.5\",value,\"1.5\"))).\n\
+         constraint(c1,\"p\").\n\
+         constraint_value(c1,0,set,(value,\"1.5\")).\n\
+         constraint_value(c1,0,set,(var,\"x\")).\n\
+         constraint_value(c1,1,array,(0,bounds,(value,\"0.5\",value,\"1.5\"))).\n\
+         constraint_value(c1,1,array,(1,empty_set)).\n\
+         constraint_value(c1,1,array,(2,set,(value,\"2.5\"))).\n"
+    );
 }
 #[test]
 fn test_constraints() {
@@ -334,83 +410,904 @@ fn test_constraints() {
             .to_string()
     );
 }
+#[test]
+fn test_int_range() {
+    let input = "var -2147483649..2147483648 : a;";
+    let mut translator = Translator::new(Options {
+        int_range: IntRangePolicy::Error,
+        ..Options::default()
+    });
+    let err = translator.write_fz_stmt(Vec::new(), input).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Integer -2147483649 in a is outside of clingo's 32-bit range"
+    );
+    let mut translator = Translator::new(Options {
+        int_range: IntRangePolicy::Warn,
+        ..Options::default()
+    });
+    let mut res = Vec::new();
+    translator.write_fz_stmt(&mut res, input).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&res).unwrap(),
+        "variable_type(\"a\",int,range,(value,-2147483649,value,2147483648)).\n"
+    );
+    assert_eq!(translator.int_range_violations(), ["a"]);
+    let mut translator = Translator::new(Options {
+        int_range: IntRangePolicy::Clamp,
+        ..Options::default()
+    });
+    let mut res = Vec::new();
+    translator.write_fz_stmt(&mut res, input).unwrap();
+    translator
+        .write_fz_stmt(&mut res, "var {1,3000000000} : b;")
+        .unwrap();
+    assert_eq!(
+        std::str::from_utf8(&res).unwrap(),
+        "variable_type(\"a\",int,range,(value,-2147483648,value,2147483647)).\n\
+         variable_type(\"b\",int,set,(value,1)).\n"
+    );
+    assert!(translator
+        .write_fz_stmt(Vec::new(), "constraint int_le(x,3000000000);")
+        .is_err());
+    let err = translator
+        .write_fz_stmt(Vec::new(), "var 3000000000..4000000000 : c;")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Integer 3000000000 in c is outside of clingo's 32-bit range"
+    );
+    assert!(translator
+        .write_fz_stmt(Vec::new(), "var set of 4000000000..3000000000 : d;")
+        .is_err());
+    let err = translator
+        .write_fz_stmt(Vec::new(), "var {3000000000,4000000000} : e;")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Integer 3000000000 in e is outside of clingo's 32-bit range"
+    );
+    assert!(translator
+        .write_fz_stmt(Vec::new(), "var set of {3000000000} : f;")
+        .is_err());
+    let mut translator = Translator::new(Options {
+        int_range: IntRangePolicy::String,
+        ..Options::default()
+    });
+    let mut res = Vec::new();
+    translator
+        .write_fz_stmt(&mut res, "constraint int_le(x,3000000000);")
+        .unwrap();
+    assert_eq!(
+        std::str::from_utf8(&res).unwrap(),
+        "constraint(c1,\"int_le\").\n\
+         constraint_value(c1,0,var,\"x\").\n\
+         constraint_value(c1,1,value,\"3000000000\").\n"
+    );
+    assert_eq!(translator.int_range_violations(), ["c1 (int_le)"]);
+}
+#[test]
+fn test_statement_order() {
+    let input = ["var int : x;", "int : a = 1;"];
+    let mut translator = Translator::default();
+    for line in input {
+        translator.write_fz_stmt(Vec::new(), line).unwrap();
+    }
+    assert!(translator.finish().is_err());
+    let mut translator = Translator::new(Options {
+        order: StatementOrder::Strict,
+        ..Options::default()
+    });
+    translator.write_fz_stmt(Vec::new(), input[0]).unwrap();
+    let err = translator.write_fz_stmt(Vec::new(), input[1]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Statements in wrong order: parameter in line 2"
+    );
+    let mut translator = Translator::new(Options {
+        order: StatementOrder::Lenient,
+        ..Options::default()
+    });
+    for line in input {
+        translator.write_fz_stmt(Vec::new(), line).unwrap();
+    }
+    translator.finish().unwrap();
+}
+#[test]
+fn test_output_array() {
+    let mut translator = Translator::default();
+    let mut res = Vec::new();
+    translator
+        .write_fz_stmt(&mut res, "set of int: S = 1..3;")
+        .unwrap();
+    translator
+        .write_fz_stmt(
+            &mut res,
+            "array [1..6] of var int : a :: output_array([S,{1,2}]);",
+        )
+        .unwrap();
+    translator
+        .write_fz_stmt(
+            &mut res,
+            "array [1..3] of var int : b :: output_array([S]);",
+        )
+        .unwrap();
+    assert_eq!(
+        std::str::from_utf8(&res).unwrap(),
+        "parameter_type(\"S\",set_of_int).\n\
+         parameter_value(\"S\",range,(value,1,value,3)).\n\
+         variable_type(\"a\",array(6,int)).\n\
+         output_array(\"a\",0,(1,3)).\n\
+         output_array(\"a\",1,(1,2)).\n\
+         variable_type(\"b\",array(3,int)).\n\
+         output_array(\"b\",0,(1,3)).\n"
+    );
+    let err = translator
+        .write_fz_stmt(
+            Vec::new(),
+            "array [1..3] of var int : c :: output_array([T]);",
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid output_array annotation of c: T is not a declared set of int parameter"
+    );
+    let err = translator
+        .write_fz_stmt(
+            Vec::new(),
+            "array [1..3] of var int : d :: output_array([{}]);",
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid output_array annotation of d: empty index set"
+    );
+    let err = translator
+        .write_fz_stmt(
+            Vec::new(),
+            "array [1..3] of var int : e :: output_array(1);",
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid output_array annotation of e: expected an array of index sets"
+    );
+    for (annotation, msg) in [
+        ("[1..n]", "index set 1..n is not a set of integer literals"),
+        ("[{1,3,3}]", "index set {1,3,3} is not contiguous"),
+    ] {
+        let stmt = format!("array [1..3] of var int : e :: output_array({annotation});");
+        let err = translator.write_fz_stmt(Vec::new(), &stmt).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("Invalid output_array annotation of e: {msg}")
+        );
+    }
+    translator
+        .write_fz_stmt(
+            Vec::new(),
+            "array [1..2] of var int : e :: output_array([{2,1,2}]);",
+        )
+        .unwrap();
+
+    let mut res = Vec::new();
+    for stmt in [
+        "array [1..2] of int: p = [5,6];",
+        "var 1..3: X_1;",
+        "array [1..3] of var int: arr :: output_array([1..3]) = [X_1,X_1,3];",
+        "array [1..2] of var int: q :: output_array([1..2]) = p;",
+        "array [1..2] of var int: r :: output_array([1..2]) = q;",
+    ] {
+        translator.write_fz_stmt(&mut res, stmt).unwrap();
+    }
+    let elements: Vec<_> = std::str::from_utf8(&res)
+        .unwrap()
+        .lines()
+        .filter(|l| l.starts_with("output_element"))
+        .collect();
+    assert_eq!(
+        elements,
+        [
+            "output_element(\"arr\",0,var,\"X_1\").",
+            "output_element(\"arr\",1,var,\"X_1\").",
+            "output_element(\"arr\",2,value,3).",
+            "output_element(\"q\",0,value,5).",
+            "output_element(\"q\",1,value,6).",
+        ]
+    );
+
+    let mut translator = Translator::new(Options {
+        output_array_elements: true,
+        ..Options::default()
+    });
+    let mut res = Vec::new();
+    translator
+        .write_fz_stmt(
+            &mut res,
+            "array [1..4] of var int : f :: output_array([1..2,{0,1}]);",
+        )
+        .unwrap();
+    translator
+        .write_fz_stmt(
+            &mut res,
+            "array [1..2] of var int : g :: output_array([3..4]);",
+        )
+        .unwrap();
+    assert_eq!(
+        std::str::from_utf8(&res).unwrap(),
+        "variable_type(\"f\",array(4,int)).\n\
+         output_array(\"f\",0,(1,2)).\n\
+         output_array(\"f\",1,(0,1)).\n\
+         output_array_dims(\"f\",2).\n\
+         output_array_element(\"f\",0,(1,0)).\n\
+         output_array_element(\"f\",1,(1,1)).\n\
+         output_array_element(\"f\",2,(2,0)).\n\
+         output_array_element(\"f\",3,(2,1)).\n\
+         variable_type(\"g\",array(2,int)).\n\
+         output_array(\"g\",0,(3,4)).\n\
+         output_array_dims(\"g\",1).\n\
+         output_array_element(\"g\",0,(3,)).\n\
+         output_array_element(\"g\",1,(4,)).\n"
+    );
+    let mut translator = Translator::new(Options::default());
+    let max = i128::MAX;
+    for (stmt, msg) in [
+        (
+            "array [1..3] of var int : h :: output_array([1..2,1..2]);".to_string(),
+            "Invalid output_array annotation of h: index sets do not match the array length 3",
+        ),
+        (
+            format!("array [1..3] of var int : h :: output_array([-{max}..{max}]);"),
+            "Invalid output_array annotation of h: index sets do not match the array length 3",
+        ),
+        (
+            format!("array [1..2] of var int : h :: output_array([{{-{max},{max}}}]);"),
+            "Invalid output_array annotation of h: index set {-170141183460469231731687303715884105727,\
+             170141183460469231731687303715884105727} is not contiguous",
+        ),
+    ] {
+        let err = translator.write_fz_stmt(Vec::new(), &stmt).unwrap_err();
+        assert_eq!(err.to_string(), msg);
+    }
+}
+#[test]
+fn test_identifiers() {
+    assert_eq!(string_term("x").to_string(), "\"x\"");
+    assert_eq!(string_term("a\"b\\c").to_string(), "\"a\\\"b\\\\c\"");
+    let mut opts = Options::default();
+    assert_eq!(ident("x", &opts).to_string(), "\"x\"");
+    opts.constants = true;
+    for (id, term) in [
+        ("x", "x"),
+        ("__x_1", "__x_1"),
+        ("xY'", "xY'"),
+        ("X", "\"X\""),
+        ("_X", "\"_X\""),
+        ("_", "\"_\""),
+        ("not", "\"not\""),
+        ("x-y", "\"x-y\""),
+        ("x\"", "\"x\\\"\""),
+        ("", "\"\""),
+    ] {
+        assert_eq!(ident(id, &opts).to_string(), term, "{id}");
+    }
+
+    let input = "predicate p(var int: x_1);\n\
+                 var 1..3: X_1;\n\
+                 var 1..3: _Y :: output_var;\n\
+                 array [1..2] of var int: xs = [X_1, _Y];\n\
+                 constraint int_le(X_1, _Y);\n\
+                 solve minimize X_1;\n";
+    let mut res = Vec::new();
+    translate(input.as_bytes(), &mut res, opts.clone()).unwrap();
+    let facts = std::str::from_utf8(&res).unwrap();
+    assert_eq!(
+        facts,
+        "predicate(\"p\").\n\
+         predicate_parameter(\"p\",0,x_1,int).\n\
+         variable_type(\"X_1\",int,range,(value,1,value,3)).\n\
+         variable_type(\"_Y\",int,range,(value,1,value,3)).\n\
+         output_var(\"_Y\").\n\
+         variable_type(xs,array(2,int)).\n\
+         variable_value(xs,array,(0,var,\"X_1\")).\n\
+         variable_value(xs,array,(1,var,\"_Y\")).\n\
+         constraint(c1,\"int_le\").\n\
+         constraint_value(c1,0,var,\"X_1\").\n\
+         constraint_value(c1,1,var,\"_Y\").\n\
+         solve(minimize,var,\"X_1\").\n"
+    );
+    let mut fzn = Vec::new();
+    reverse::reverse(facts.as_bytes(), &mut fzn).unwrap();
+    let mut again = Vec::new();
+    translate(fzn.as_slice(), &mut again, opts).unwrap();
+    assert_eq!(std::str::from_utf8(&again).unwrap(), facts);
+}
+/// Translates a single statement with the default options, keeping only the constraint counter
+/// and the statement order between calls.
+///
+/// Every call starts a new [`Translator`], so parameters of earlier statements are unknown, e.g.
+/// for index sets of `output_array` annotations, and errors report line 1. Use
+/// [`Translator::write_fz_stmt`] to translate the statements of one model.
 pub fn write_fz_stmt(
-    mut out: impl Write,
+    out: impl Write,
     input: &str,
     constraint_counter: &mut usize,
     level: &mut i32,
 ) -> Result<()> {
+    let mut translator = Translator {
+        constraint_counter: *constraint_counter,
+        level: *level,
+        ..Translator::default()
+    };
+    let res = translator.write_fz_stmt(out, input);
+    *constraint_counter = translator.constraint_counter;
+    *level = translator.level;
+    res
+}
+
+/// Translates a FlatZinc model, see [`input::statements`] for how it is split into statements.
+///
+/// The returned translator has seen the whole input, call [`Translator::finish`] to check that
+/// it was complete.
+///
+/// With [`Options::presolve`] or [`Options::eliminate_aliases`] the whole model is parsed
+/// before anything is written. With [`Options::parallel`] the statements are translated on
+/// multiple threads, see [`parallel`].
+pub fn translate(input: impl BufRead, out: impl Write, options: Options) -> Result<Translator> {
+    translate_stmts(input::read_statements(input), out, options)
+}
+/// Translates a FlatZinc model held in memory, see [`translate`].
+///
+/// The statements are parsed in place without copying them.
+pub fn translate_str(text: &str, out: impl Write, options: Options) -> Result<Translator> {
+    translate_stmts(input::statements(text).map(Ok), out, options)
+}
+/// Translates statements given with their line.
+fn translate_stmts<S: AsRef<str> + Sync>(
+    mut stmts: impl Iterator<Item = Result<(usize, S)>>,
+    mut out: impl Write,
+    options: Options,
+) -> Result<Translator> {
+    let mut translator = Translator::new(options);
+    if !translator.options.presolve && !translator.options.eliminate_aliases {
+        if translator.options.parallel {
+            parallel::translate(&mut translator, stmts, &mut out)?;
+            return Ok(translator);
+        }
+        for stmt in stmts.by_ref() {
+            let (line, stmt) = stmt?;
+            translator.line = line;
+            let stmt = parse_stmt(stmt.as_ref())?;
+            translator.write_stmt(&mut out, stmt)?;
+        }
+        return Ok(translator);
+    }
+    let mut parsed = vec![];
+    for stmt in stmts {
+        let (line, stmt) = stmt?;
+        parsed.push((line, parse_stmt(stmt.as_ref())?));
+    }
+    let mut stmts = parsed;
+    let aliases = if translator.options.eliminate_aliases {
+        alias::eliminate(&mut stmts)
+    } else {
+        vec![]
+    };
+    if translator.options.presolve {
+        translator.presolve = Some(presolve::presolve(&mut stmts));
+    }
+    if translator.options.parallel {
+        parallel::write_stmts(&mut translator, stmts, &mut out)?;
+    } else {
+        for (line, stmt) in stmts {
+            translator.line = line;
+            translator.write_stmt(&mut out, stmt)?;
+        }
+    }
+    let opts = &translator.options;
+    for a in aliases {
+        let id = ident(&a.id, opts);
+        writeln!(out, "alias({id},{}).", ident(&a.representative, opts))?;
+        if a.output {
+            writeln!(out, "output_var({id}).")?;
+        }
+    }
+    Ok(translator)
+}
+fn parse_stmt(input: &str) -> Result<Stmt> {
     match <Stmt as std::str::FromStr>::from_str(input) {
-        Ok(stmt) => {
-            match stmt {
-                Stmt::Comment(s) => {
-                    writeln!(out, "%{s}")?;
+        Ok(stmt) => Ok(stmt),
+        Err(msg) => Err(FlatZincError::ParseError { msg }.into()),
+    }
+}
+
+/// How integers that do not fit into clingo's 32-bit integers are handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum IntRangePolicy {
+    /// Fail on the first statement containing such an integer
+    Error,
+    /// Emit the integers unchanged and report the affected identifiers
+    #[default]
+    Warn,
+    /// Clamp domain bounds to the 32-bit range and drop set elements outside of it, values and
+    /// domains without any value in range are still errors
+    Clamp,
+    /// Emit the integers as strings
+    String,
+}
+
+/// How strictly the statement order of FlatZinc is enforced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StatementOrder {
+    /// Warn about statements in wrong order
+    #[default]
+    Warn,
+    /// Statements in wrong order are errors
+    Strict,
+    /// Warn about statements in wrong order and accept models without solve item
+    Lenient,
+}
+
+/// Target format of the translation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputMode {
+    /// Facts only
+    #[default]
+    Facts,
+    /// Linear constraints and integer domains as clingcon theory atoms, facts for the rest
+    Clingcon,
+    /// Difference constraints and integer bounds as clingo-dl theory atoms, facts for the rest
+    Dl,
+}
+
+/// Options controlling the translation.
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    pub int_range: IntRangePolicy,
+    pub order: StatementOrder,
+    pub mode: OutputMode,
+    /// Write the domains of integer variables as `dom/2` facts
+    pub domains: bool,
+    /// Write `dom_min/2`, `dom_max/2` and `dom_next/3` along with the domains
+    pub order_encoding: bool,
+    /// Move unary constraints into the domains of their variables, see [`presolve`]
+    pub presolve: bool,
+    /// Replace variables equal to other variables, see [`alias`]
+    pub eliminate_aliases: bool,
+    /// Write identifiers of variables and parameters as constants where possible
+    pub constants: bool,
+    /// Translate the statements on multiple threads, see [`parallel`]
+    pub parallel: bool,
+    /// Write constant sets of integers with at most this many elements as `elem` terms, and the
+    /// size of every constant set of integers as `set_size` term
+    pub expand_sets: Option<usize>,
+    /// Write `output_array_dims/2` and the coordinates of the elements of output arrays as
+    /// `output_array_element/3`
+    pub output_array_elements: bool,
+}
+
+/// Translates FlatZinc statements one by one, keeping track of the state between them.
+#[derive(Debug, Default)]
+pub struct Translator {
+    options: Options,
+    constraint_counter: usize,
+    level: i32,
+    line: usize,
+    warnings: usize,
+    int_range_violations: Vec<String>,
+    /// Parameters looked up by later statements, see [`Translator::keep_param`]
+    params: HashMap<String, ParDeclItem>,
+    presolve: Option<presolve::Report>,
+}
+impl Translator {
+    pub fn new(options: Options) -> Translator {
+        Translator {
+            options,
+            ..Translator::default()
+        }
+    }
+    pub fn write_fz_stmt(&mut self, out: impl Write, input: &str) -> Result<()> {
+        self.line += 1;
+        let stmt = parse_stmt(input)?;
+        self.write_stmt(out, stmt)
+    }
+    fn write_stmt(&mut self, out: impl Write, stmt: Stmt) -> Result<()> {
+        let i = self.check_stmt(&stmt)?;
+        self.write_checked(out, &stmt, i)?;
+        if let Stmt::Parameter(p) = stmt {
+            self.keep_param(p);
+        }
+        Ok(())
+    }
+    /// Keeps the parameters later statements look up: integers assigned to integer variables,
+    /// sets of integers used as index sets of `output_array`, and arrays of every type, which
+    /// give the `output_element` facts of the output arrays defined by them. Booleans and floats
+    /// are never looked up.
+    fn keep_param(&mut self, p: ParDeclItem) {
+        match p {
+            ParDeclItem::Bool { .. } | ParDeclItem::Float { .. } => {}
+            ParDeclItem::Int { .. }
+            | ParDeclItem::SetOfInt { .. }
+            | ParDeclItem::ArrayOfBool { .. }
+            | ParDeclItem::ArrayOfInt { .. }
+            | ParDeclItem::ArrayOfFloat { .. }
+            | ParDeclItem::ArrayOfSet { .. } => {
+                self.params.insert(par_decl_id(&p).to_string(), p);
+            }
+        }
+    }
+    /// Checks a statement and counts constraints, returns the number of the last constraint.
+    fn check_stmt(&mut self, stmt: &Stmt) -> Result<usize> {
+        self.check_int_range(stmt)?;
+        self.check_order(stmt)?;
+        if let Stmt::Constraint(_) = stmt {
+            self.constraint_counter += 1;
+        }
+        Ok(self.constraint_counter)
+    }
+    /// Writes a checked statement, a constraint gets the id `c{i}`.
+    fn write_checked(&self, mut out: impl Write, stmt: &Stmt, i: usize) -> Result<()> {
+        let opts = &self.options;
+        match stmt {
+            Stmt::Comment(s) => {
+                writeln!(out, "%{s}")?;
+            }
+            Stmt::Predicate(pred) => {
+                write_predicate(out, pred, opts)?;
+            }
+            Stmt::Parameter(p) => {
+                write_par_decl_item(out, p, opts)?;
+            }
+            Stmt::Variable(d) => {
+                write_var_decl_item(&mut out, d, opts, &self.params)?;
+                write_output_elements(&mut out, d, opts, &self.params)?;
+                if opts.domains {
+                    domain::write_domain(&mut out, d, opts, &self.params)?;
                 }
-                Stmt::Predicate(pred) => {
-                    if *level > 1 {
-                        warn!("Statements in wrong order.");
-                    }
-                    write_predicate(out, &pred)?;
+                match opts.mode {
+                    OutputMode::Facts => {}
+                    OutputMode::Clingcon => clingcon::write_var_decl(out, d, opts, &self.params)?,
+                    OutputMode::Dl => dl::write_var_decl(out, d, opts, &self.params)?,
                 }
-                Stmt::Parameter(p) => {
-                    if *level > 2 {
-                        warn!("Statements in wrong order.");
-                    } else {
-                        *level = 2;
+            }
+            Stmt::Constraint(c) => {
+                let written = match opts.mode {
+                    OutputMode::Facts => false,
+                    OutputMode::Clingcon => {
+                        clingcon::write_constraint(&mut out, c, opts, &self.params)?
                     }
-                    write_par_decl_item(out, &p)?;
+                    OutputMode::Dl => dl::write_constraint(&mut out, c, opts, &self.params)?,
+                };
+                if !written {
+                    write_constraint(out, c, i, opts)?;
                 }
-                Stmt::Variable(d) => {
-                    if *level > 3 {
-                        warn!("Statements in wrong order.");
-                    } else {
-                        *level = 3;
-                    }
-                    write_var_decl_item(out, &d)?;
+            }
+            Stmt::SolveItem(item) => {
+                write_solve_item(out, item, opts)?;
+            }
+        }
+        Ok(())
+    }
+    /// Checks that the input is complete and reports identifiers with integers that did not
+    /// fit into clingo's integer range.
+    pub fn finish(&self) -> Result<()> {
+        if !self.int_range_violations.is_empty() {
+            warn!(
+                "Integers outside of clingo's 32-bit range in: {}",
+                self.int_range_violations.join(", ")
+            );
+        }
+        if self.level < 5 && self.options.order != StatementOrder::Lenient {
+            return Err(FlatZincError::NoSolveItem.into());
+        }
+        Ok(())
+    }
+    /// What the presolve pass changed, if it was enabled.
+    pub fn presolve_report(&self) -> Option<&presolve::Report> {
+        self.presolve.as_ref()
+    }
+    /// Identifiers of the statements that contained integers outside of clingo's range.
+    pub fn int_range_violations(&self) -> &[String] {
+        &self.int_range_violations
+    }
+    /// Number of warnings issued so far, including the report of integers out of range.
+    pub fn warnings(&self) -> usize {
+        self.warnings + usize::from(!self.int_range_violations.is_empty())
+    }
+    /// Checks that predicates, parameters, variables, constraints and the solve item appear in
+    /// this order.
+    fn check_order(&mut self, stmt: &Stmt) -> Result<()> {
+        let (level, kind) = match stmt {
+            Stmt::Comment(_) => return Ok(()),
+            Stmt::Predicate(_) => (1, "predicate"),
+            Stmt::Parameter(_) => (2, "parameter"),
+            Stmt::Variable(_) => (3, "variable"),
+            Stmt::Constraint(_) => (4, "constraint"),
+            Stmt::SolveItem(_) => {
+                if self.level > 4 {
+                    return Err(FlatZincError::MultipleSolveItems.into());
                 }
-                Stmt::Constraint(c) => {
-                    if *level > 4 {
-                        warn!("Statements in wrong order.");
-                    } else {
-                        *level = 4;
+                self.level = 5;
+                return Ok(());
+            }
+        };
+        if self.level <= level {
+            self.level = level;
+        } else if self.options.order == StatementOrder::Strict {
+            return Err(FlatZincError::WrongOrder {
+                kind,
+                line: self.line,
+            }
+            .into());
+        } else {
+            warn!("Statements in wrong order: {kind} in line {}.", self.line);
+            self.warnings += 1;
+        }
+        Ok(())
+    }
+    fn check_int_range(&mut self, stmt: &Stmt) -> Result<()> {
+        let mut ints = vec![];
+        stmt_ints(stmt, &mut ints);
+        let mut out_of_range = ints.into_iter().filter(|(i, _)| !fits_clingo_int(*i));
+        let Some((value, domain)) = out_of_range.next() else {
+            return Ok(());
+        };
+        let id = match stmt {
+            Stmt::Constraint(c) => format!("c{} ({})", self.constraint_counter + 1, c.id),
+            _ => stmt_id(stmt).to_string(),
+        };
+        let value = match self.options.int_range {
+            IntRangePolicy::Error => Some(value),
+            IntRangePolicy::Clamp if !domain => Some(value),
+            IntRangePolicy::Clamp => out_of_range.find(|(_, domain)| !domain).map(|(i, _)| i),
+            IntRangePolicy::Warn | IntRangePolicy::String => None,
+        };
+        if let Some(value) = value {
+            return Err(FlatZincError::IntOutOfRange { id, value }.into());
+        }
+        self.int_range_violations.push(id);
+        Ok(())
+    }
+}
+
+/// Identifier of a declaration or the name of a constraint.
+fn stmt_id(stmt: &Stmt) -> &str {
+    match stmt {
+        Stmt::Comment(_) => "comment",
+        Stmt::Predicate(p) => &p.id,
+        Stmt::Parameter(p) => par_decl_id(p),
+        Stmt::Variable(v) => var_decl_id(v),
+        Stmt::Constraint(c) => &c.id,
+        Stmt::SolveItem(_) => "solve",
+    }
+}
+fn par_decl_id(item: &ParDeclItem) -> &str {
+    match item {
+        ParDeclItem::Bool { id, .. }
+        | ParDeclItem::Int { id, .. }
+        | ParDeclItem::Float { id, .. }
+        | ParDeclItem::SetOfInt { id, .. }
+        | ParDeclItem::ArrayOfBool { id, .. }
+        | ParDeclItem::ArrayOfInt { id, .. }
+        | ParDeclItem::ArrayOfFloat { id, .. }
+        | ParDeclItem::ArrayOfSet { id, .. } => id,
+    }
+}
+fn var_decl_id(item: &VarDeclItem) -> &str {
+    match item {
+        VarDeclItem::Bool { id, .. }
+        | VarDeclItem::Int { id, .. }
+        | VarDeclItem::IntInRange { id, .. }
+        | VarDeclItem::IntInSet { id, .. }
+        | VarDeclItem::Float { id, .. }
+        | VarDeclItem::BoundedFloat { id, .. }
+        | VarDeclItem::SetOfInt { id, .. }
+        | VarDeclItem::SubSetOfIntSet { id, .. }
+        | VarDeclItem::SubSetOfIntRange { id, .. }
+        | VarDeclItem::ArrayOfBool { id, .. }
+        | VarDeclItem::ArrayOfInt { id, .. }
+        | VarDeclItem::ArrayOfIntInRange { id, .. }
+        | VarDeclItem::ArrayOfIntInSet { id, .. }
+        | VarDeclItem::ArrayOfFloat { id, .. }
+        | VarDeclItem::ArrayOfBoundedFloat { id, .. }
+        | VarDeclItem::ArrayOfSet { id, .. }
+        | VarDeclItem::ArrayOfSubSetOfIntRange { id, .. }
+        | VarDeclItem::ArrayOfSubSetOfIntSet { id, .. } => id,
+    }
+}
+/// Collects the integers of a statement, flagging those that bound a domain.
+fn stmt_ints(stmt: &Stmt, ints: &mut Vec<(i128, bool)>) {
+    match stmt {
+        Stmt::Comment(_) => {}
+        Stmt::Predicate(pred) => {
+            for (par_type, _) in &pred.parameters {
+                match par_type {
+                    PredParType::Basic(t) | PredParType::Array { par_type: t, .. } => {
+                        pred_par_type_ints(t, ints)
                     }
-                    *constraint_counter += 1;
-                    write_constraint(out, &c, *constraint_counter)?;
                 }
-                Stmt::SolveItem(i) => {
-                    if *level > 4 {
-                        return Err(FlatZincError::MultipleSolveItems.into());
-                    }
-                    *level = 5;
-                    write_solve_item(out, &i)?;
+            }
+        }
+        Stmt::Parameter(item) => match item {
+            ParDeclItem::Int { int, .. } => ints.push((*int, false)),
+            ParDeclItem::SetOfInt { set_literal, .. } => set_literal_ints(set_literal, ints),
+            ParDeclItem::ArrayOfInt { v, .. } => ints.extend(v.iter().map(|i| (*i, false))),
+            ParDeclItem::ArrayOfSet { v, .. } => {
+                for sl in v {
+                    set_literal_ints(sl, ints)
                 }
             }
-            Ok(())
+            _ => {}
+        },
+        Stmt::Variable(item) => var_decl_ints(item, ints),
+        Stmt::Constraint(c) => {
+            for e in &c.exprs {
+                expr_ints(e, ints)
+            }
         }
-        Err(msg) => Err(FlatZincError::ParseError { msg }.into()),
+        Stmt::SolveItem(i) => match &i.goal {
+            Goal::OptimizeInt(_, e) => int_expr_ints(e, ints),
+            Goal::OptimizeSet(_, e) => set_expr_ints(e, ints),
+            _ => {}
+        },
+    }
+}
+fn pred_par_type_ints(t: &BasicPredParType, ints: &mut Vec<(i128, bool)>) {
+    match t {
+        BasicPredParType::BasicVarType(t) => var_type_ints(t, ints),
+        BasicPredParType::IntInRange(lb, ub) | BasicPredParType::SubSetOfIntRange(lb, ub) => {
+            range_ints(*lb, *ub, ints)
+        }
+        BasicPredParType::IntInSet(set) | BasicPredParType::SubSetOfIntSet(set) => {
+            set_ints(set, ints)
+        }
+        _ => {}
+    }
+}
+fn var_type_ints(t: &BasicVarType, ints: &mut Vec<(i128, bool)>) {
+    match t {
+        BasicVarType::IntInRange(lb, ub) | BasicVarType::SubSetOfIntRange(lb, ub) => {
+            range_ints(*lb, *ub, ints)
+        }
+        BasicVarType::IntInSet(set) | BasicVarType::SubSetOfIntSet(set) => set_ints(set, ints),
+        _ => {}
+    }
+}
+fn var_decl_ints(item: &VarDeclItem, ints: &mut Vec<(i128, bool)>) {
+    match item {
+        VarDeclItem::IntInRange { lb, ub, .. }
+        | VarDeclItem::SubSetOfIntRange { lb, ub, .. }
+        | VarDeclItem::ArrayOfIntInRange { lb, ub, .. }
+        | VarDeclItem::ArrayOfSubSetOfIntRange { lb, ub, .. } => range_ints(*lb, *ub, ints),
+        VarDeclItem::IntInSet { set, .. }
+        | VarDeclItem::SubSetOfIntSet { set, .. }
+        | VarDeclItem::ArrayOfIntInSet { set, .. }
+        | VarDeclItem::ArrayOfSubSetOfIntSet { set, .. } => set_ints(set, ints),
+        _ => {}
+    }
+    match item {
+        VarDeclItem::Int { expr: Some(e), .. }
+        | VarDeclItem::IntInRange { expr: Some(e), .. }
+        | VarDeclItem::IntInSet { expr: Some(e), .. } => int_expr_ints(e, ints),
+        VarDeclItem::SetOfInt { expr: Some(e), .. }
+        | VarDeclItem::SubSetOfIntSet { expr: Some(e), .. }
+        | VarDeclItem::SubSetOfIntRange { expr: Some(e), .. } => set_expr_ints(e, ints),
+        VarDeclItem::ArrayOfInt {
+            array_expr: Some(ArrayOfIntExpr::Array(v)),
+            ..
+        }
+        | VarDeclItem::ArrayOfIntInRange {
+            array_expr: Some(ArrayOfIntExpr::Array(v)),
+            ..
+        }
+        | VarDeclItem::ArrayOfIntInSet {
+            array_expr: Some(ArrayOfIntExpr::Array(v)),
+            ..
+        } => {
+            for e in v {
+                int_expr_ints(e, ints)
+            }
+        }
+        VarDeclItem::ArrayOfSet {
+            array_expr: Some(ArrayOfSetExpr::Array(v)),
+            ..
+        }
+        | VarDeclItem::ArrayOfSubSetOfIntRange {
+            array_expr: Some(ArrayOfSetExpr::Array(v)),
+            ..
+        }
+        | VarDeclItem::ArrayOfSubSetOfIntSet {
+            array_expr: Some(ArrayOfSetExpr::Array(v)),
+            ..
+        } => {
+            for e in v {
+                set_expr_ints(e, ints)
+            }
+        }
+        _ => {}
+    }
+}
+/// The bounds of a domain, which can only be clamped if the range meets clingo's range.
+fn range_ints(lb: i128, ub: i128, ints: &mut Vec<(i128, bool)>) {
+    let clampable = (fits_clingo_int(lb) && fits_clingo_int(ub))
+        || (lb <= ub && lb <= i32::MAX.into() && ub >= i32::MIN.into());
+    ints.extend([(lb, clampable), (ub, clampable)])
+}
+/// The elements of a domain, which can only be clamped if one of them is in clingo's range.
+fn set_ints(set: &[i128], ints: &mut Vec<(i128, bool)>) {
+    let clampable = set.iter().any(|i| fits_clingo_int(*i));
+    ints.extend(set.iter().map(|i| (*i, clampable)))
+}
+fn expr_ints(e: &Expr, ints: &mut Vec<(i128, bool)>) {
+    match e {
+        Expr::Int(i) => ints.push((*i, false)),
+        Expr::Set(sl) => set_literal_expr_ints(sl, ints),
+        Expr::ArrayOfInt(v) => {
+            for e in v {
+                int_expr_ints(e, ints)
+            }
+        }
+        Expr::ArrayOfSet(v) => {
+            for e in v {
+                set_expr_ints(e, ints)
+            }
+        }
+        _ => {}
+    }
+}
+fn int_expr_ints(e: &IntExpr, ints: &mut Vec<(i128, bool)>) {
+    if let IntExpr::Int(i) = e {
+        ints.push((*i, false))
+    }
+}
+fn set_expr_ints(e: &SetExpr, ints: &mut Vec<(i128, bool)>) {
+    if let SetExpr::Set(sl) = e {
+        set_literal_expr_ints(sl, ints)
+    }
+}
+fn set_literal_expr_ints(sl: &SetLiteralExpr, ints: &mut Vec<(i128, bool)>) {
+    match sl {
+        SetLiteralExpr::IntInRange(lb, ub) => {
+            int_expr_ints(lb, ints);
+            int_expr_ints(ub, ints)
+        }
+        SetLiteralExpr::SetInts(v) => {
+            for e in v {
+                int_expr_ints(e, ints)
+            }
+        }
+        _ => {}
+    }
+}
+fn set_literal_ints(sl: &SetLiteral, ints: &mut Vec<(i128, bool)>) {
+    match sl {
+        SetLiteral::IntRange(lb, ub) => ints.extend([(*lb, false), (*ub, false)]),
+        SetLiteral::SetInts(v) => ints.extend(v.iter().map(|i| (*i, false))),
+        _ => {}
     }
 }
 
-fn write_predicate(mut buf: impl Write, predicate: &PredicateItem) -> Result<()> {
-    writeln!(buf, "predicate(\"{}\").", predicate.id)?;
+fn write_predicate(mut buf: impl Write, predicate: &PredicateItem, opts: &Options) -> Result<()> {
+    let predicate_id = string_term(&predicate.id);
+    writeln!(buf, "predicate({predicate_id}).")?;
     for (pos, p) in predicate.parameters.iter().enumerate() {
         match p {
             (PredParType::Basic(par_type), id) => {
-                for element in basic_pred_par_type(par_type) {
+                for element in basic_pred_par_type(par_type, opts) {
                     writeln!(
                         buf,
-                        "predicate_parameter(\"{}\",{pos},\"{id}\",{element}).",
-                        predicate.id
+                        "predicate_parameter({predicate_id},{pos},{},{element}).",
+                        ident(id, opts)
                     )?;
                 }
             }
             (PredParType::Array { ix, par_type }, id) => {
-                for element in basic_pred_par_type(par_type) {
+                for element in basic_pred_par_type(par_type, opts) {
                     writeln!(
                         buf,
-                        "predicate_parameter(\"{}\",{pos},\"{id}\",{}).",
-                        predicate.id,
-                        array_type(&pred_index(ix), &element)
+                        "predicate_parameter({predicate_id},{pos},{},{}).",
+                        ident(id, opts),
+                        array_type(pred_index(ix), element)
                     )?;
                 }
             }
@@ -418,78 +1315,93 @@ fn write_predicate(mut buf: impl Write, predicate: &PredicateItem) -> Result<()>
     }
     Ok(())
 }
-fn write_par_decl_item(mut buf: impl Write, item: &ParDeclItem) -> Result<()> {
+fn write_par_decl_item(mut buf: impl Write, item: &ParDeclItem, opts: &Options) -> Result<()> {
+    let name = ident(par_decl_id(item), opts);
+    writeln!(buf, "parameter_type({name},{}).", par_type(item))?;
     match item {
-        ParDeclItem::Bool { id, bool } => {
+        ParDeclItem::Bool { bool, .. } => {
             writeln!(
                 buf,
-                "parameter_value(\"{id}\",value,{}).",
+                "parameter_value({name},value,{}).",
                 bool_literal(*bool)
             )?;
         }
-        ParDeclItem::Int { id, int } => {
-            writeln!(buf, "parameter_value(\"{id}\",value,{int}).")?;
+        ParDeclItem::Int { int, .. } => {
+            writeln!(
+                buf,
+                "parameter_value({name},value,{}).",
+                int_literal(*int, opts)
+            )?;
         }
-        ParDeclItem::Float { id, float } => {
-            writeln!(buf, "parameter_value(\"{id}\",value,\"{float}\").")?;
+        ParDeclItem::Float { float, .. } => {
+            writeln!(buf, "parameter_value({name},value,\"{float}\").")?;
         }
         ParDeclItem::SetOfInt {
-            id,
-            set_literal: sl,
+            set_literal: sl, ..
         } => {
-            let set = dec_set_literal(sl);
+            let set = dec_set_literal(sl, opts);
             for element in set {
-                writeln!(buf, "parameter_value(\"{id}\",{element}).")?;
+                writeln!(buf, "parameter_value({name},{element}).")?;
             }
         }
-        ParDeclItem::ArrayOfBool { ix: _, id, v } => {
+        ParDeclItem::ArrayOfBool { v, .. } => {
             for (pos, e) in v.iter().enumerate() {
                 writeln!(
                     buf,
-                    "parameter_value(\"{id}\",array,({pos},value,{})).",
+                    "parameter_value({name},array,({pos},value,{})).",
                     bool_literal(*e)
                 )?;
             }
         }
-        ParDeclItem::ArrayOfInt { ix: _, id, v } => {
+        ParDeclItem::ArrayOfInt { v, .. } => {
             for (pos, int) in v.iter().enumerate() {
-                writeln!(buf, "parameter_value(\"{id}\",array,({pos},value,{int})).")?;
+                writeln!(
+                    buf,
+                    "parameter_value({name},array,({pos},value,{})).",
+                    int_literal(*int, opts)
+                )?;
             }
         }
-        ParDeclItem::ArrayOfFloat { ix: _, id, v } => {
+        ParDeclItem::ArrayOfFloat { v, .. } => {
             for (pos, float) in v.iter().enumerate() {
                 writeln!(
                     buf,
-                    "parameter_value(\"{id}\",array,({pos},value,\"{float}\"))."
+                    "parameter_value({name},array,({pos},value,\"{float}\"))."
                 )?;
             }
         }
-        ParDeclItem::ArrayOfSet { ix: _, id, v } => {
+        ParDeclItem::ArrayOfSet { v, .. } => {
             for (pos, e) in v.iter().enumerate() {
-                let set = dec_set_literal(e);
+                let set = dec_set_literal(e, opts);
                 for element in set {
-                    writeln!(buf, "parameter_value(\"{id}\",array,({pos},{element})).")?;
+                    writeln!(buf, "parameter_value({name},array,({pos},{element})).")?;
                 }
             }
         }
     }
     Ok(())
 }
-fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
+fn write_var_decl_item(
+    mut buf: impl Write,
+    item: &VarDeclItem,
+    opts: &Options,
+    params: &HashMap<String, ParDeclItem>,
+) -> Result<()> {
+    let name = ident(var_decl_id(item), opts);
     match item {
         VarDeclItem::Bool { id, expr, annos } => {
-            writeln!(buf, "variable_type(\"{id}\",bool).")?;
+            writeln!(buf, "variable_type({name},bool).")?;
             if let Some(expr) = expr {
-                writeln!(buf, "variable_value(\"{id}\",{}).", bool_expr(expr))?;
+                writeln!(buf, "variable_value({name},{}).", bool_expr(expr, opts))?;
             }
-            write_output_var(buf, id, annos)?;
+            write_output_var(buf, id, annos, opts)?;
         }
         VarDeclItem::Int { id, expr, annos } => {
-            writeln!(buf, "variable_type(\"{id}\",int).")?;
+            writeln!(buf, "variable_type({name},int).")?;
             if let Some(expr) = expr {
-                writeln!(buf, "variable_value(\"{id}\",{}).", int_expr(expr))?;
+                writeln!(buf, "variable_value({name},{}).", int_expr(expr, opts))?;
             }
-            write_output_var(buf, id, annos)?;
+            write_output_var(buf, id, annos, opts)?;
         }
         VarDeclItem::IntInRange {
             id,
@@ -498,11 +1410,11 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
             expr,
             annos,
         } => {
-            writeln!(buf, "variable_type(\"{id}\",{}).", int_in_range(lb, ub))?;
+            writeln!(buf, "variable_type({name},{}).", int_in_range(lb, ub, opts))?;
             if let Some(expr) = expr {
-                writeln!(buf, "variable_value(\"{id}\",{}).", int_expr(expr))?;
+                writeln!(buf, "variable_value({name},{}).", int_expr(expr, opts))?;
             }
-            write_output_var(buf, id, annos)?;
+            write_output_var(buf, id, annos, opts)?;
         }
         VarDeclItem::IntInSet {
             id,
@@ -510,20 +1422,20 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
             expr,
             annos,
         } => {
-            for element in int_in_set(set) {
-                writeln!(buf, "variable_type(\"{id}\",{element}).")?;
+            for element in int_in_set(set, opts) {
+                writeln!(buf, "variable_type({name},{element}).")?;
             }
             if let Some(expr) = expr {
-                writeln!(buf, "variable_value(\"{id}\",{}).", int_expr(expr))?;
+                writeln!(buf, "variable_value({name},{}).", int_expr(expr, opts))?;
             }
-            write_output_var(buf, id, annos)?;
+            write_output_var(buf, id, annos, opts)?;
         }
         VarDeclItem::Float { id, expr, annos } => {
-            writeln!(buf, "variable_type(\"{id}\",float).")?;
+            writeln!(buf, "variable_type({name},float).")?;
             if let Some(expr) = expr {
-                writeln!(buf, "variable_value(\"{id}\",{}).", float_expr(expr))?;
+                writeln!(buf, "variable_value({name},{}).", float_expr(expr, opts))?;
             }
-            write_output_var(buf, id, annos)?;
+            write_output_var(buf, id, annos, opts)?;
         }
         VarDeclItem::BoundedFloat {
             id,
@@ -532,21 +1444,21 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
             expr,
             annos,
         } => {
-            writeln!(buf, "variable_type(\"{id}\",{}).", bounded_float(*lb, *ub))?;
+            writeln!(buf, "variable_type({name},{}).", bounded_float(*lb, *ub))?;
             if let Some(expr) = expr {
-                writeln!(buf, "variable_value(\"{id}\",{}).", float_expr(expr))?;
+                writeln!(buf, "variable_value({name},{}).", float_expr(expr, opts))?;
             }
-            write_output_var(buf, id, annos)?;
+            write_output_var(buf, id, annos, opts)?;
         }
         VarDeclItem::SetOfInt { id, annos, expr } => {
-            writeln!(buf, "variable_type(\"{id}\",set_of_int).")?;
+            writeln!(buf, "variable_type({name},set_of_int).")?;
             if let Some(expr) = expr {
-                let set = dec_set_expr(expr);
+                let set = dec_set_expr(expr, opts);
                 for element in set {
-                    writeln!(buf, "variable_value(\"{id}\",{element}).")?;
+                    writeln!(buf, "variable_value({name},{element}).")?;
                 }
             }
-            write_output_var(buf, id, annos)?;
+            write_output_var(buf, id, annos, opts)?;
         }
         VarDeclItem::SubSetOfIntRange {
             id,
@@ -557,16 +1469,16 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
         } => {
             writeln!(
                 buf,
-                "variable_type(\"{id}\",{}).",
-                subset_of_int_range(lb, ub),
+                "variable_type({name},{}).",
+                subset_of_int_range(lb, ub, opts),
             )?;
             if let Some(expr) = expr {
-                let set = dec_set_expr(expr);
+                let set = dec_set_expr(expr, opts);
                 for element in set {
-                    writeln!(buf, "variable_value(\"{id}\",{element}).")?;
+                    writeln!(buf, "variable_value({name},{element}).")?;
                 }
             }
-            write_output_var(buf, id, annos)?;
+            write_output_var(buf, id, annos, opts)?;
         }
         VarDeclItem::SubSetOfIntSet {
             id,
@@ -574,19 +1486,16 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
             expr,
             annos,
         } => {
-            for element in set {
-                writeln!(
-                    buf,
-                    "variable_type(\"{id}\",set_of_int,set,(value,{element}))."
-                )?;
+            for element in subset_of_int_set(set, opts) {
+                writeln!(buf, "variable_type({name},{element}).")?;
             }
             if let Some(expr) = expr {
-                let set = dec_set_expr(expr);
+                let set = dec_set_expr(expr, opts);
                 for element in set {
-                    writeln!(buf, "variable_value(\"{id}\",{element}).")?;
+                    writeln!(buf, "variable_value({name},{element}).")?;
                 }
             }
-            write_output_var(buf, id, annos)?;
+            write_output_var(buf, id, annos, opts)?;
         }
         VarDeclItem::ArrayOfBool {
             id,
@@ -596,25 +1505,25 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
         } => {
             writeln!(
                 buf,
-                "variable_type(\"{id}\",{}).",
-                array_type(&index(ix), "bool")
+                "variable_type({name},{}).",
+                array_type(index(ix), "bool")
             )?;
             match array_expr {
                 Some(ArrayOfBoolExpr::Array(v)) => {
                     for (pos, e) in v.iter().enumerate() {
                         writeln!(
                             buf,
-                            "variable_value(\"{id}\",array,({pos},{})).",
-                            bool_expr(e)
+                            "variable_value({name},array,({pos},{})).",
+                            bool_expr(e, opts)
                         )?;
                     }
                 }
                 Some(ArrayOfBoolExpr::VarParIdentifier(id2)) => {
-                    writeln!(buf, "variable_value(\"{id}\",value,\"{id2}\").")?;
+                    writeln!(buf, "variable_value({name},value,{}).", ident(id2, opts))?;
                 }
                 None => {}
             }
-            write_output_array(buf, id, annos)?;
+            write_output_array(buf, id, ix, annos, params, opts)?;
         }
         VarDeclItem::ArrayOfInt {
             id,
@@ -624,25 +1533,25 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
         } => {
             writeln!(
                 buf,
-                "variable_type(\"{id}\",{}).",
-                array_type(&index(ix), "int")
+                "variable_type({name},{}).",
+                array_type(index(ix), "int")
             )?;
             match array_expr {
                 Some(ArrayOfIntExpr::Array(v)) => {
                     for (pos, e) in v.iter().enumerate() {
                         writeln!(
                             buf,
-                            "variable_value(\"{id}\",array,({pos},{})).",
-                            int_expr(e)
+                            "variable_value({name},array,({pos},{})).",
+                            int_expr(e, opts)
                         )?;
                     }
                 }
                 Some(ArrayOfIntExpr::VarParIdentifier(id2)) => {
-                    writeln!(buf, "variable_value(\"{id}\",value,\"{id2}\").")?;
+                    writeln!(buf, "variable_value({name},value,{}).", ident(id2, opts))?;
                 }
                 None => {}
             }
-            write_output_array(buf, id, annos)?;
+            write_output_array(buf, id, ix, annos, params, opts)?;
         }
         VarDeclItem::ArrayOfIntInRange {
             id,
@@ -654,25 +1563,25 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
         } => {
             writeln!(
                 buf,
-                "variable_type(\"{id}\",{}).",
-                array_type(&index(ix), &int_in_range(lb, ub)),
+                "variable_type({name},{}).",
+                array_type(index(ix), int_in_range(lb, ub, opts)),
             )?;
             match array_expr {
                 Some(ArrayOfIntExpr::Array(v)) => {
                     for (pos, e) in v.iter().enumerate() {
                         writeln!(
                             buf,
-                            "variable_value(\"{id}\",array,({pos},{})).",
-                            int_expr(e)
+                            "variable_value({name},array,({pos},{})).",
+                            int_expr(e, opts)
                         )?;
                     }
                 }
                 Some(ArrayOfIntExpr::VarParIdentifier(id2)) => {
-                    writeln!(buf, "variable_value(\"{id}\",value,\"{id2}\").")?;
+                    writeln!(buf, "variable_value({name},value,{}).", ident(id2, opts))?;
                 }
                 None => {}
             }
-            write_output_array(buf, id, annos)?;
+            write_output_array(buf, id, ix, annos, params, opts)?;
         }
         VarDeclItem::ArrayOfIntInSet {
             id,
@@ -681,11 +1590,11 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
             array_expr,
             annos,
         } => {
-            for element in int_in_set(set) {
+            for element in int_in_set(set, opts) {
                 writeln!(
                     buf,
-                    "variable_type(\"{id}\",{}).",
-                    array_type(&index(ix), &element)
+                    "variable_type({name},{}).",
+                    array_type(index(ix), element)
                 )?;
             }
             match array_expr {
@@ -693,17 +1602,17 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
                     for (pos, e) in v.iter().enumerate() {
                         writeln!(
                             buf,
-                            "variable_value(\"{id}\",array,({pos},{})).",
-                            int_expr(e)
+                            "variable_value({name},array,({pos},{})).",
+                            int_expr(e, opts)
                         )?;
                     }
                 }
                 Some(ArrayOfIntExpr::VarParIdentifier(id2)) => {
-                    writeln!(buf, "variable_value(\"{id}\",value,\"{id2}\").")?;
+                    writeln!(buf, "variable_value({name},value,{}).", ident(id2, opts))?;
                 }
                 None => {}
             }
-            write_output_array(buf, id, annos)?;
+            write_output_array(buf, id, ix, annos, params, opts)?;
         }
         VarDeclItem::ArrayOfFloat {
             id,
@@ -713,25 +1622,25 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
         } => {
             writeln!(
                 buf,
-                "variable_type(\"{id}\",{}).",
-                array_type(&index(ix), "float")
+                "variable_type({name},{}).",
+                array_type(index(ix), "float")
             )?;
             match array_expr {
                 Some(ArrayOfFloatExpr::Array(v)) => {
                     for (pos, e) in v.iter().enumerate() {
                         writeln!(
                             buf,
-                            "variable_value(\"{id}\",array,({pos},{})).",
-                            float_expr(e)
+                            "variable_value({name},array,({pos},{})).",
+                            float_expr(e, opts)
                         )?;
                     }
                 }
                 Some(ArrayOfFloatExpr::VarParIdentifier(id2)) => {
-                    writeln!(buf, "variable_value(\"{id}\",value,\"{id2}\").")?;
+                    writeln!(buf, "variable_value({name},value,{}).", ident(id2, opts))?;
                 }
                 None => {}
             }
-            write_output_array(buf, id, annos)?;
+            write_output_array(buf, id, ix, annos, params, opts)?;
         }
         VarDeclItem::ArrayOfBoundedFloat {
             id,
@@ -743,25 +1652,25 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
         } => {
             writeln!(
                 buf,
-                "variable_type(\"{id}\",{}).",
-                array_type(&index(ix), &bounded_float(*lb, *ub)),
+                "variable_type({name},{}).",
+                array_type(index(ix), bounded_float(*lb, *ub)),
             )?;
             match array_expr {
                 Some(ArrayOfFloatExpr::Array(v)) => {
                     for (pos, e) in v.iter().enumerate() {
                         writeln!(
                             buf,
-                            "variable_value(\"{id}\",array,({pos},{})).",
-                            float_expr(e)
+                            "variable_value({name},array,({pos},{})).",
+                            float_expr(e, opts)
                         )?;
                     }
                 }
                 Some(ArrayOfFloatExpr::VarParIdentifier(id2)) => {
-                    writeln!(buf, "variable_value(\"{id}\",value,\"{id2}\").")?;
+                    writeln!(buf, "variable_value({name},value,{}).", ident(id2, opts))?;
                 }
                 None => {}
             }
-            write_output_array(buf, id, annos)?;
+            write_output_array(buf, id, ix, annos, params, opts)?;
         }
         VarDeclItem::ArrayOfSet {
             id,
@@ -771,24 +1680,24 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
         } => {
             writeln!(
                 buf,
-                "variable_type(\"{id}\",{}).",
-                array_type(&index(ix), "set")
+                "variable_type({name},{}).",
+                array_type(index(ix), "set")
             )?;
             match array_expr {
                 Some(ArrayOfSetExpr::Array(v)) => {
                     for (pos, e) in v.iter().enumerate() {
-                        let set = dec_set_expr(e);
+                        let set = dec_set_expr(e, opts);
                         for element in set {
-                            writeln!(buf, "variable_value(\"{id}\",array,({pos},{element})).")?;
+                            writeln!(buf, "variable_value({name},array,({pos},{element})).")?;
                         }
                     }
                 }
                 Some(ArrayOfSetExpr::VarParIdentifier(id2)) => {
-                    writeln!(buf, "variable_value(\"{id}\",value,\"{id2}\").")?;
+                    writeln!(buf, "variable_value({name},value,{}).", ident(id2, opts))?;
                 }
                 None => {}
             }
-            write_output_array(buf, id, annos)?;
+            write_output_array(buf, id, ix, annos, params, opts)?;
         }
         VarDeclItem::ArrayOfSubSetOfIntRange {
             id,
@@ -800,24 +1709,24 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
         } => {
             writeln!(
                 buf,
-                "variable_type(\"{id}\",{}).",
-                array_type(&index(ix), &subset_of_int_range(lb, ub))
+                "variable_type({name},{}).",
+                array_type(index(ix), subset_of_int_range(lb, ub, opts))
             )?;
             match array_expr {
                 Some(ArrayOfSetExpr::Array(v)) => {
                     for (pos, e) in v.iter().enumerate() {
-                        let set = dec_set_expr(e);
+                        let set = dec_set_expr(e, opts);
                         for element in set {
-                            writeln!(buf, "variable_value(\"{id}\",array,({pos},{element})).")?;
+                            writeln!(buf, "variable_value({name},array,({pos},{element})).")?;
                         }
                     }
                 }
                 Some(ArrayOfSetExpr::VarParIdentifier(id2)) => {
-                    writeln!(buf, "variable_value(\"{id}\",value,\"{id2}\").")?;
+                    writeln!(buf, "variable_value({name},value,{}).", ident(id2, opts))?;
                 }
                 None => {}
             }
-            write_output_array(buf, id, annos)?;
+            write_output_array(buf, id, ix, annos, params, opts)?;
         }
         VarDeclItem::ArrayOfSubSetOfIntSet {
             id,
@@ -826,79 +1735,101 @@ fn write_var_decl_item(mut buf: impl Write, item: &VarDeclItem) -> Result<()> {
             array_expr,
             annos,
         } => {
-            for element in set {
+            for element in subset_of_int_set(set, opts) {
                 writeln!(
                     buf,
-                    "variable_type(\"{id}\",{}).",
-                    array_type(&index(ix), &format!("set_of_int,set,(value,{element})")),
+                    "variable_type({name},{}).",
+                    array_type(index(ix), element),
                 )?;
             }
             match array_expr {
                 Some(ArrayOfSetExpr::Array(v)) => {
                     for (pos, se) in v.iter().enumerate() {
-                        for e in dec_set_expr(se) {
-                            writeln!(buf, "variable_value(\"{id}\",array,({pos},{e})).")?;
+                        for e in dec_set_expr(se, opts) {
+                            writeln!(buf, "variable_value({name},array,({pos},{e})).")?;
                         }
                     }
                 }
                 Some(ArrayOfSetExpr::VarParIdentifier(id2)) => {
-                    writeln!(buf, "variable_value(\"{id}\",value,\"{id2}\").")?;
+                    writeln!(buf, "variable_value({name},value,{}).", ident(id2, opts))?;
                 }
                 None => {}
             }
-            write_output_array(buf, id, annos)?;
+            write_output_array(buf, id, ix, annos, params, opts)?;
         }
     }
     Ok(())
 }
-fn basic_var_type(t: &BasicVarType) -> Vec<String> {
+fn basic_var_type(t: &BasicVarType, opts: &Options) -> Vec<String> {
     match t {
         BasicVarType::BasicType(BasicType::Bool) => vec!["bool".to_string()],
         BasicVarType::BasicType(BasicType::Int) => vec!["int".to_string()],
-        BasicVarType::IntInRange(lb, ub) => vec![int_in_range(lb, ub)],
-        BasicVarType::IntInSet(set) => int_in_set(set),
+        BasicVarType::IntInRange(lb, ub) => vec![int_in_range(lb, ub, opts)],
+        BasicVarType::IntInSet(set) => int_in_set(set, opts).map(|t| t.to_string()).collect(),
         BasicVarType::BasicType(BasicType::Float) => vec!["float".to_string()],
         BasicVarType::BoundedFloat(lb, ub) => vec![bounded_float(*lb, *ub)],
-        BasicVarType::SubSetOfIntRange(lb, ub) => vec![subset_of_int_range(lb, ub)],
-        BasicVarType::SubSetOfIntSet(set) => subset_of_int_set(set),
+        BasicVarType::SubSetOfIntRange(lb, ub) => vec![subset_of_int_range(lb, ub, opts)],
+        BasicVarType::SubSetOfIntSet(set) => subset_of_int_set(set, opts)
+            .map(|t| t.to_string())
+            .collect(),
     }
 }
-fn int_in_range(lb: &i128, ub: &i128) -> String {
-    format!("int,range,(value,{lb},value,{ub})")
+fn int_in_range(lb: &i128, ub: &i128, opts: &Options) -> String {
+    format!(
+        "int,range,(value,{},value,{})",
+        int_bound(*lb, opts),
+        int_bound(*ub, opts)
+    )
 }
-fn int_in_set(set: &[i128]) -> Vec<String> {
-    let mut ret = vec![];
-    for integer in set {
-        ret.push(format!("int,set,(value,{integer})"))
-    }
-    ret
+fn int_in_set<'a>(set: &'a [i128], opts: &'a Options) -> impl Iterator<Item = impl Display + 'a> {
+    set_type("int", set, opts)
 }
 fn float_in_set(set: &[f64]) -> Vec<String> {
     let mut ret = vec![];
     for float in set {
-        ret.push(format!("float_in_set(\"{float}\")"))
+        ret.push(format!("float,set,(value,\"{float}\")"))
     }
     ret
 }
 fn bounded_float(lb: f64, ub: f64) -> String {
-    format!("float,(bounds,value,\"{lb}\",value,\"{ub}\")")
+    format!("float,bounds,(value,\"{lb}\",value,\"{ub}\")")
 }
-fn subset_of_int_range(lb: &i128, ub: &i128) -> String {
-    format!("set_of_int,range,(value,{lb},value,{ub})")
+fn subset_of_int_range(lb: &i128, ub: &i128, opts: &Options) -> String {
+    format!(
+        "set_of_int,range,(value,{},value,{})",
+        int_bound(*lb, opts),
+        int_bound(*ub, opts)
+    )
 }
-fn subset_of_int_set(set: &[i128]) -> Vec<String> {
-    let mut ret = vec![];
-    for i in set {
-        ret.push(format!("set_of_int,set,(value,{i})"))
-    }
-    ret
+fn subset_of_int_set<'a>(
+    set: &'a [i128],
+    opts: &'a Options,
+) -> impl Iterator<Item = impl Display + 'a> {
+    set_type("set_of_int", set, opts)
+}
+/// Elements of a domain given as set, dropping unrepresentable ones if domains are clamped.
+fn domain_elements<'a>(set: &'a [i128], opts: &'a Options) -> impl Iterator<Item = &'a i128> {
+    set.iter()
+        .filter(|i| opts.int_range != IntRangePolicy::Clamp || fits_clingo_int(**i))
 }
-fn write_constraint(mut buf: impl Write, c: &ConstraintItem, i: usize) -> Result<()> {
-    writeln!(buf, "constraint(c{i},\"{}\").", c.id)?;
+fn fits_clingo_int(int: i128) -> bool {
+    i32::try_from(int).is_ok()
+}
+fn write_constraint(
+    mut buf: impl Write,
+    c: &ConstraintItem,
+    i: usize,
+    opts: &Options,
+) -> Result<()> {
+    writeln!(buf, "constraint(c{i},{}).", string_term(&c.id))?;
     for (cpos, ce) in c.exprs.iter().enumerate() {
         match ce {
             Expr::VarParIdentifier(id) => {
-                writeln!(buf, "constraint_value(c{i},{cpos},var,\"{id}\").")?;
+                writeln!(
+                    buf,
+                    "constraint_value(c{i},{cpos},var,{}).",
+                    ident(id, opts)
+                )?;
             }
             Expr::Bool(e) => {
                 writeln!(
@@ -908,13 +1839,17 @@ fn write_constraint(mut buf: impl Write, c: &ConstraintItem, i: usize) -> Result
                 )?;
             }
             Expr::Int(int) => {
-                writeln!(buf, "constraint_value(c{i},{cpos},value,{int}).")?;
+                writeln!(
+                    buf,
+                    "constraint_value(c{i},{cpos},value,{}).",
+                    int_literal(*int, opts)
+                )?;
             }
             Expr::Float(float) => {
                 writeln!(buf, "constraint_value(c{i},{cpos},value,\"{float}\").")?;
             }
             Expr::Set(e) => {
-                let set = dec_set_literal_expr(e);
+                let set = dec_set_literal_expr(e, opts);
                 for element in set {
                     writeln!(buf, "constraint_value(c{i},{cpos},{element}).")?;
                 }
@@ -924,7 +1859,7 @@ fn write_constraint(mut buf: impl Write, c: &ConstraintItem, i: usize) -> Result
                     writeln!(
                         buf,
                         "constraint_value(c{i},{cpos},array,({apos},{})).",
-                        bool_expr(ae)
+                        bool_expr(ae, opts)
                     )?;
                 }
             }
@@ -933,7 +1868,7 @@ fn write_constraint(mut buf: impl Write, c: &ConstraintItem, i: usize) -> Result
                     writeln!(
                         buf,
                         "constraint_value(c{i},{cpos},array,({apos},{})).",
-                        int_expr(ae)
+                        int_expr(ae, opts)
                     )?;
                 }
             }
@@ -942,13 +1877,13 @@ fn write_constraint(mut buf: impl Write, c: &ConstraintItem, i: usize) -> Result
                     writeln!(
                         buf,
                         "constraint_value(c{i},{cpos},array,({apos},{})).",
-                        float_expr(ae)
+                        float_expr(ae, opts)
                     )?;
                 }
             }
             Expr::ArrayOfSet(v) => {
                 for (apos, ae) in v.iter().enumerate() {
-                    let set = dec_set_expr(ae);
+                    let set = dec_set_expr(ae, opts);
                     for element in set {
                         writeln!(
                             buf,
@@ -961,22 +1896,22 @@ fn write_constraint(mut buf: impl Write, c: &ConstraintItem, i: usize) -> Result
     }
     Ok(())
 }
-fn write_solve_item(mut buf: impl Write, i: &SolveItem) -> Result<()> {
+fn write_solve_item(mut buf: impl Write, i: &SolveItem, opts: &Options) -> Result<()> {
     match &i.goal {
         Goal::Satisfy => {
             writeln!(buf, "solve(satisfy).")?;
         }
         Goal::OptimizeBool(ot, e) => {
-            writeln!(buf, "solve({},{}).", opt_type(ot), bool_expr(e))?;
+            writeln!(buf, "solve({},{}).", opt_type(ot), bool_expr(e, opts))?;
         }
         Goal::OptimizeInt(ot, e) => {
-            writeln!(buf, "solve({},{}).", opt_type(ot), int_expr(e))?;
+            writeln!(buf, "solve({},{}).", opt_type(ot), int_expr(e, opts))?;
         }
         Goal::OptimizeFloat(ot, e) => {
-            writeln!(buf, "solve({},{}).", opt_type(ot), float_expr(e))?;
+            writeln!(buf, "solve({},{}).", opt_type(ot), float_expr(e, opts))?;
         }
         Goal::OptimizeSet(ot, e) => {
-            let set = dec_set_expr(e);
+            let set = dec_set_expr(e, opts);
             for element in set {
                 writeln!(buf, "solve({},{element}).", opt_type(ot))?;
             }
@@ -992,20 +1927,34 @@ fn basic_par_type(t: &BasicParType) -> String {
         BasicParType::SetOfInt => "set_of_int".to_string(),
     }
 }
-fn basic_pred_par_type(t: &BasicPredParType) -> Vec<String> {
+fn basic_pred_par_type(t: &BasicPredParType, opts: &Options) -> Vec<String> {
     match t {
         BasicPredParType::BasicParType(t) => vec![basic_par_type(t)],
-        BasicPredParType::BasicVarType(t) => basic_var_type(t),
+        BasicPredParType::BasicVarType(t) => basic_var_type(t, opts),
         BasicPredParType::VarSetOfInt => vec!["set_of_int".to_string()],
         BasicPredParType::BoundedFloat(lb, ub) => vec![bounded_float(*lb, *ub)],
-        BasicPredParType::IntInRange(lb, ub) => vec![int_in_range(lb, ub)],
-        BasicPredParType::IntInSet(set) => int_in_set(set),
+        BasicPredParType::IntInRange(lb, ub) => vec![int_in_range(lb, ub, opts)],
+        BasicPredParType::IntInSet(set) => int_in_set(set, opts).map(|t| t.to_string()).collect(),
         BasicPredParType::FloatInSet(set) => float_in_set(set),
-        BasicPredParType::SubSetOfIntRange(lb, ub) => vec![subset_of_int_range(lb, ub)],
-        BasicPredParType::SubSetOfIntSet(set) => subset_of_int_set(set),
+        BasicPredParType::SubSetOfIntRange(lb, ub) => vec![subset_of_int_range(lb, ub, opts)],
+        BasicPredParType::SubSetOfIntSet(set) => subset_of_int_set(set, opts)
+            .map(|t| t.to_string())
+            .collect(),
+    }
+}
+fn par_type(item: &ParDeclItem) -> String {
+    match item {
+        ParDeclItem::Bool { .. } => "bool".to_string(),
+        ParDeclItem::Int { .. } => "int".to_string(),
+        ParDeclItem::Float { .. } => "float".to_string(),
+        ParDeclItem::SetOfInt { .. } => "set_of_int".to_string(),
+        ParDeclItem::ArrayOfBool { ix, .. } => array_type(index(ix), "bool"),
+        ParDeclItem::ArrayOfInt { ix, .. } => array_type(index(ix), "int"),
+        ParDeclItem::ArrayOfFloat { ix, .. } => array_type(index(ix), "float"),
+        ParDeclItem::ArrayOfSet { ix, .. } => array_type(index(ix), "set_of_int"),
     }
 }
-fn array_type(idx: &str, element_type: &str) -> String {
+fn array_type(idx: impl Display, element_type: impl Display) -> String {
     format!("array({idx},{element_type})")
 }
 fn opt_type(opt_type: &OptimizationType) -> String {
@@ -1023,125 +1972,353 @@ fn pred_index(is: &PredIndexSet) -> String {
         PredIndexSet::Int => "int".to_string(),
     }
 }
-fn bool_expr(e: &BoolExpr) -> String {
-    match e {
-        BoolExpr::Bool(b) => format!("value,{}", bool_literal(*b)),
-        BoolExpr::VarParIdentifier(id) => format!("var,\"{id}\""),
-    }
-}
-fn bool_literal(b: bool) -> String {
-    if b {
-        "true".to_string()
-    } else {
-        "false".to_string()
-    }
-}
-fn int_expr(e: &IntExpr) -> String {
-    match e {
-        IntExpr::Int(int) => format!("value,{int}"),
-        IntExpr::VarParIdentifier(id) => format!("var,\"{id}\""),
-    }
-}
-fn float_expr(e: &FloatExpr) -> String {
-    match e {
-        FloatExpr::Float(float) => format!("value,\"{float}\""),
-        FloatExpr::VarParIdentifier(id) => format!("var,\"{id}\""),
-    }
-}
-fn dec_set_expr(e: &SetExpr) -> Vec<String> {
-    match e {
-        SetExpr::Set(sl) => dec_set_literal_expr(sl),
-        SetExpr::VarParIdentifier(id) => vec![format!("var,\"{id}\"")],
+fn write_output_var(
+    mut buf: impl Write,
+    id: &str,
+    annos: &[Annotation],
+    opts: &Options,
+) -> Result<()> {
+    for a in annos {
+        if a.id == "output_var" {
+            writeln!(buf, "output_var({}).", ident(id, opts))?;
+            break;
+        }
     }
+    Ok(())
 }
-fn dec_set_literal_expr(l: &SetLiteralExpr) -> Vec<String> {
-    let mut ret = Vec::new();
-    match l {
-        SetLiteralExpr::BoundedFloat(f1, f2) => {
-            ret.push(format!("bounds,({},{})", float_expr(f1), float_expr(f2)))
-        }
-        SetLiteralExpr::IntInRange(i1, i2) => {
-            ret.push(format!("range,({},{})", int_expr(i1), int_expr(i2)))
-        }
-        SetLiteralExpr::SetFloats(v) => {
-            if v.is_empty() {
-                ret.push("empty_set".to_string());
-            } else {
-                for f in v {
-                    ret.push(format!("set,({})", float_expr(f)));
+fn write_output_array(
+    mut buf: impl Write,
+    id: &str,
+    IndexSet(len): &IndexSet,
+    annos: &[Annotation],
+    params: &HashMap<String, ParDeclItem>,
+    opts: &Options,
+) -> Result<()> {
+    for a in annos {
+        if a.id == "output_array" {
+            let index_sets = match a.expressions.first() {
+                Some(AnnExpr::Expr(e)) => output_array_index_sets(e),
+                _ => None,
+            };
+            let Some(index_sets) = index_sets else {
+                return Err(FlatZincError::InvalidOutputArray {
+                    id: id.to_string(),
+                    msg: "expected an array of index sets".to_string(),
                 }
+                .into());
+            };
+            let name = ident(id, opts);
+            let mut bounds = Vec::with_capacity(index_sets.len());
+            for (pos, e) in index_sets.into_iter().enumerate() {
+                let (lb, ub) = index_set_bounds(e, params).map_err(|msg| {
+                    FlatZincError::InvalidOutputArray {
+                        id: id.to_string(),
+                        msg,
+                    }
+                })?;
+                writeln!(buf, "output_array({name},{pos},({lb},{ub})).")?;
+                bounds.push((lb, ub));
             }
-        }
-        SetLiteralExpr::SetInts(v) => {
-            if v.is_empty() {
-                ret.push("empty_set".to_string());
-            } else {
-                for i in v {
-                    ret.push(format!("set,({})", int_expr(i)));
+            let size = bounds
+                .iter()
+                .try_fold(1i128, |size, (lb, ub)| {
+                    let n = if lb > ub {
+                        0
+                    } else {
+                        ub.checked_sub(*lb)?.checked_add(1)?
+                    };
+                    size.checked_mul(n)
+                })
+                .filter(|size| !bounds.is_empty() && size == len);
+            if size.is_none() {
+                return Err(FlatZincError::InvalidOutputArray {
+                    id: id.to_string(),
+                    msg: format!("index sets do not match the array length {len}"),
                 }
+                .into());
+            }
+            if opts.output_array_elements {
+                write_output_array_elements(buf, name, &bounds, *len)?;
             }
+            break;
         }
     }
-    ret
+    Ok(())
 }
-fn dec_set_literal(l: &SetLiteral) -> Vec<String> {
-    let mut ret = Vec::new();
-    match l {
-        SetLiteral::BoundedFloat(f1, f2) => {
-            ret.push(format!("bounds,(value,\"{f1}\",value,\"{f2}\")"))
-        }
-        SetLiteral::IntRange(i1, i2) => ret.push(format!("range,(value,{i1},value,{i2})")),
-        SetLiteral::SetFloats(v) => {
-            if v.is_empty() {
-                ret.push("empty_set".to_string());
-            } else {
-                for f in v {
-                    ret.push(format!("set,(value,\"{f}\")"));
+/// Writes the elements of an array annotated with `output_array` as `output_element/4`.
+///
+/// An array defined by a parameter array gets the values of the parameter, one defined by
+/// another variable array gets no facts.
+fn write_output_elements(
+    mut buf: impl Write,
+    item: &VarDeclItem,
+    opts: &Options,
+    params: &HashMap<String, ParDeclItem>,
+) -> Result<()> {
+    let annos = match item {
+        VarDeclItem::ArrayOfBool { annos, .. }
+        | VarDeclItem::ArrayOfInt { annos, .. }
+        | VarDeclItem::ArrayOfIntInRange { annos, .. }
+        | VarDeclItem::ArrayOfIntInSet { annos, .. }
+        | VarDeclItem::ArrayOfFloat { annos, .. }
+        | VarDeclItem::ArrayOfBoundedFloat { annos, .. }
+        | VarDeclItem::ArrayOfSet { annos, .. }
+        | VarDeclItem::ArrayOfSubSetOfIntRange { annos, .. }
+        | VarDeclItem::ArrayOfSubSetOfIntSet { annos, .. } => annos,
+        _ => return Ok(()),
+    };
+    if !annos.iter().any(|a| a.id == "output_array") {
+        return Ok(());
+    }
+    let name = ident(var_decl_id(item), opts);
+    let mut write = |pos: usize, e: &dyn Display| -> Result<()> {
+        writeln!(buf, "output_element({name},{pos},{e}).")?;
+        Ok(())
+    };
+    let alias = match item {
+        VarDeclItem::ArrayOfBool {
+            array_expr: Some(ArrayOfBoolExpr::Array(v)),
+            ..
+        } => {
+            for (pos, e) in v.iter().enumerate() {
+                write(pos, &bool_expr(e, opts))?;
+            }
+            None
+        }
+        VarDeclItem::ArrayOfInt {
+            array_expr: Some(ArrayOfIntExpr::Array(v)),
+            ..
+        }
+        | VarDeclItem::ArrayOfIntInRange {
+            array_expr: Some(ArrayOfIntExpr::Array(v)),
+            ..
+        }
+        | VarDeclItem::ArrayOfIntInSet {
+            array_expr: Some(ArrayOfIntExpr::Array(v)),
+            ..
+        } => {
+            for (pos, e) in v.iter().enumerate() {
+                write(pos, &int_expr(e, opts))?;
+            }
+            None
+        }
+        VarDeclItem::ArrayOfFloat {
+            array_expr: Some(ArrayOfFloatExpr::Array(v)),
+            ..
+        }
+        | VarDeclItem::ArrayOfBoundedFloat {
+            array_expr: Some(ArrayOfFloatExpr::Array(v)),
+            ..
+        } => {
+            for (pos, e) in v.iter().enumerate() {
+                write(pos, &float_expr(e, opts))?;
+            }
+            None
+        }
+        VarDeclItem::ArrayOfSet {
+            array_expr: Some(ArrayOfSetExpr::Array(v)),
+            ..
+        }
+        | VarDeclItem::ArrayOfSubSetOfIntRange {
+            array_expr: Some(ArrayOfSetExpr::Array(v)),
+            ..
+        }
+        | VarDeclItem::ArrayOfSubSetOfIntSet {
+            array_expr: Some(ArrayOfSetExpr::Array(v)),
+            ..
+        } => {
+            for (pos, se) in v.iter().enumerate() {
+                for e in dec_set_expr(se, opts) {
+                    write(pos, &e)?;
                 }
             }
+            None
+        }
+        VarDeclItem::ArrayOfBool {
+            array_expr: Some(ArrayOfBoolExpr::VarParIdentifier(id)),
+            ..
+        }
+        | VarDeclItem::ArrayOfInt {
+            array_expr: Some(ArrayOfIntExpr::VarParIdentifier(id)),
+            ..
+        }
+        | VarDeclItem::ArrayOfIntInRange {
+            array_expr: Some(ArrayOfIntExpr::VarParIdentifier(id)),
+            ..
+        }
+        | VarDeclItem::ArrayOfIntInSet {
+            array_expr: Some(ArrayOfIntExpr::VarParIdentifier(id)),
+            ..
+        }
+        | VarDeclItem::ArrayOfFloat {
+            array_expr: Some(ArrayOfFloatExpr::VarParIdentifier(id)),
+            ..
         }
-        SetLiteral::SetInts(v) => {
-            if v.is_empty() {
-                ret.push("empty_set".to_string());
-            } else {
-                for f in v {
-                    ret.push(format!("set,(value,{f})"));
+        | VarDeclItem::ArrayOfBoundedFloat {
+            array_expr: Some(ArrayOfFloatExpr::VarParIdentifier(id)),
+            ..
+        }
+        | VarDeclItem::ArrayOfSet {
+            array_expr: Some(ArrayOfSetExpr::VarParIdentifier(id)),
+            ..
+        }
+        | VarDeclItem::ArrayOfSubSetOfIntRange {
+            array_expr: Some(ArrayOfSetExpr::VarParIdentifier(id)),
+            ..
+        }
+        | VarDeclItem::ArrayOfSubSetOfIntSet {
+            array_expr: Some(ArrayOfSetExpr::VarParIdentifier(id)),
+            ..
+        } => Some(id),
+        _ => None,
+    };
+    match alias.and_then(|id| params.get(id)) {
+        Some(ParDeclItem::ArrayOfBool { v, .. }) => {
+            for (pos, b) in v.iter().enumerate() {
+                write(pos, &Scalar::Bool(*b))?;
+            }
+        }
+        Some(ParDeclItem::ArrayOfInt { v, .. }) => {
+            for (pos, int) in v.iter().enumerate() {
+                write(pos, &Scalar::Int(int_literal(*int, opts)))?;
+            }
+        }
+        Some(ParDeclItem::ArrayOfFloat { v, .. }) => {
+            for (pos, float) in v.iter().enumerate() {
+                write(pos, &Scalar::Float(*float))?;
+            }
+        }
+        Some(ParDeclItem::ArrayOfSet { v, .. }) => {
+            for (pos, sl) in v.iter().enumerate() {
+                for e in dec_set_literal(sl, opts) {
+                    write(pos, &e)?;
                 }
             }
         }
+        _ => {}
     }
-    ret
+    Ok(())
 }
-fn write_output_var(mut buf: impl Write, id: &str, annos: &[Annotation]) -> Result<()> {
-    for a in annos {
-        if a.id == "output_var" {
-            writeln!(buf, "output_var(\"{id}\").")?;
+/// Writes the number of dimensions of an output array and the coordinates of its elements in
+/// row-major order.
+fn write_output_array_elements(
+    mut buf: impl Write,
+    name: Ident,
+    bounds: &[(i128, i128)],
+    len: i128,
+) -> Result<()> {
+    writeln!(buf, "output_array_dims({name},{}).", bounds.len())?;
+    if len == 0 {
+        return Ok(());
+    }
+    let mut index: Vec<_> = bounds.iter().map(|(lb, _)| *lb).collect();
+    for flat in 0.. {
+        let coordinates: Vec<_> = index.iter().map(i128::to_string).collect();
+        let comma = if index.len() == 1 { "," } else { "" };
+        writeln!(
+            buf,
+            "output_array_element({name},{flat},({}{comma})).",
+            coordinates.join(",")
+        )?;
+        let Some(d) = (0..index.len()).rev().find(|&d| index[d] < bounds[d].1) else {
             break;
+        };
+        index[d] += 1;
+        for (i, (lb, _)) in index[d + 1..].iter_mut().zip(&bounds[d + 1..]) {
+            *i = *lb;
         }
     }
     Ok(())
 }
-fn write_output_array(mut buf: impl Write, id: &str, annos: &[Annotation]) -> Result<()> {
-    for a in annos {
-        if a.id == "output_array" {
-            match a.expressions.first() {
-                Some(AnnExpr::Expr(Expr::ArrayOfSet(v))) => {
-                    for (pos, e) in v.iter().enumerate() {
-                        match e {
-                            SetExpr::Set(SetLiteralExpr::IntInRange(
-                                IntExpr::Int(lb),
-                                IntExpr::Int(ub),
-                            )) => {
-                                writeln!(buf, "output_array(\"{id}\",{pos},({lb},{ub})).")?;
-                            }
-                            x => panic!("unexpected set expr: {x:?}"),
-                        }
-                    }
+/// The index sets of an `output_array` annotation.
+///
+/// An array consisting only of identifiers is parsed as array of bools, so identifiers are
+/// accepted from every kind of array.
+fn output_array_index_sets(e: &Expr) -> Option<Vec<SetExpr>> {
+    let identifiers = |ids: Option<Vec<&String>>| {
+        ids.map(|ids| {
+            ids.into_iter()
+                .map(|id| SetExpr::VarParIdentifier(id.clone()))
+                .collect()
+        })
+    };
+    match e {
+        Expr::ArrayOfSet(v) => Some(v.clone()),
+        Expr::ArrayOfBool(v) => identifiers(
+            v.iter()
+                .map(|e| match e {
+                    BoolExpr::VarParIdentifier(id) => Some(id),
+                    BoolExpr::Bool(_) => None,
+                })
+                .collect(),
+        ),
+        Expr::ArrayOfInt(v) => identifiers(
+            v.iter()
+                .map(|e| match e {
+                    IntExpr::VarParIdentifier(id) => Some(id),
+                    IntExpr::Int(_) => None,
+                })
+                .collect(),
+        ),
+        Expr::ArrayOfFloat(v) => identifiers(
+            v.iter()
+                .map(|e| match e {
+                    FloatExpr::VarParIdentifier(id) => Some(id),
+                    FloatExpr::Float(_) => None,
+                })
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+/// Lower and upper bound of an index set, resolving identifiers of `set of int` parameters.
+fn index_set_bounds(
+    e: SetExpr,
+    params: &HashMap<String, ParDeclItem>,
+) -> std::result::Result<(i128, i128), String> {
+    let ints = match e {
+        SetExpr::Set(SetLiteralExpr::IntInRange(IntExpr::Int(lb), IntExpr::Int(ub))) => {
+            return Ok((lb, ub))
+        }
+        SetExpr::Set(SetLiteralExpr::SetInts(v)) => v
+            .iter()
+            .map(|e| match e {
+                IntExpr::Int(i) => Ok(*i),
+                IntExpr::VarParIdentifier(id) => {
+                    Err(format!("index set element {id} is not an integer literal"))
                 }
-                _ => panic!("expected an array of index sets!"),
-            }
-            break;
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?,
+        SetExpr::VarParIdentifier(set_id) => match params.get(&set_id) {
+            Some(ParDeclItem::SetOfInt {
+                set_literal: SetLiteral::IntRange(lb, ub),
+                ..
+            }) => return Ok((*lb, *ub)),
+            Some(ParDeclItem::SetOfInt {
+                set_literal: SetLiteral::SetInts(v),
+                ..
+            }) => v.clone(),
+            _ => return Err(format!("{set_id} is not a declared set of int parameter")),
+        },
+        e => {
+            let e = format::set_expr(&e);
+            return Err(format!("index set {e} is not a set of integer literals"));
         }
+    };
+    let mut distinct = ints.clone();
+    distinct.sort_unstable();
+    distinct.dedup();
+    match (distinct.first(), distinct.last()) {
+        (Some(lb), Some(ub))
+            if ub.checked_sub(*lb).and_then(|d| d.checked_add(1))
+                == Some(distinct.len() as i128) =>
+        {
+            Ok((*lb, *ub))
+        }
+        (Some(_), Some(_)) => Err(format!(
+            "index set {} is not contiguous",
+            format::int_set(&ints)
+        )),
+        _ => Err("empty index set".to_string()),
     }
-    Ok(())
 }
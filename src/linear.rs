@@ -0,0 +1,260 @@
+//! Recognition of linear integer constraints for the theory outputs.
+use crate::fits_clingo_int;
+use flatzinc::{BoolExpr, ConstraintItem, Expr, IntExpr, ParDeclItem};
+use std::collections::HashMap;
+
+/// Relation between the sum and the right hand side of a linear constraint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Relation {
+    Eq,
+    Ne,
+    Le,
+    Gt,
+}
+impl Relation {
+    pub(crate) fn negate(self) -> Relation {
+        match self {
+            Relation::Eq => Relation::Ne,
+            Relation::Ne => Relation::Eq,
+            Relation::Le => Relation::Gt,
+            Relation::Gt => Relation::Le,
+        }
+    }
+    pub(crate) fn symbol(self) -> &'static str {
+        match self {
+            Relation::Eq => "=",
+            Relation::Ne => "!=",
+            Relation::Le => "<=",
+            Relation::Gt => ">",
+        }
+    }
+}
+
+/// A Boolean constant or variable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Literal {
+    Const(bool),
+    Var(String),
+}
+
+/// How the truth of a constraint is tied to a Boolean.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Reification {
+    None,
+    /// The constraint holds iff the literal is true
+    Equiv(Literal),
+    /// The constraint holds if the literal is true
+    Imp(Literal),
+}
+
+/// The constraint `sum(coefficient * variable) relation rhs`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Linear {
+    pub(crate) terms: Vec<(i128, String)>,
+    pub(crate) relation: Relation,
+    pub(crate) rhs: i128,
+    pub(crate) reification: Reification,
+}
+
+impl Linear {
+    /// Whether the coefficients and the right-hand side fit into clingo integers, as required
+    /// in theory atoms.
+    pub(crate) fn fits_clingo(&self) -> bool {
+        fits_clingo_int(self.rhs) && self.terms.iter().all(|(a, _)| fits_clingo_int(*a))
+    }
+}
+
+enum Term {
+    Const(i128),
+    Var(String),
+}
+
+/// Recognizes `int_lin_eq/ne/le`, `int_eq/ne/le/lt`, their reified and half-reified forms,
+/// `int_plus` and `int_times` with a constant factor.
+///
+/// Identifiers of integer parameters and arrays of integer parameters are resolved, any
+/// other argument that is not a variable makes the constraint non-linear, as does an overflow
+/// while collecting the constants on the right-hand side.
+pub(crate) fn linear(c: &ConstraintItem, params: &HashMap<String, ParDeclItem>) -> Option<Linear> {
+    let (name, reified) = match (c.id.strip_suffix("_reif"), c.id.strip_suffix("_imp")) {
+        (Some(name), _) => (name, Some(true)),
+        (_, Some(name)) => (name, Some(false)),
+        _ => (c.id.as_str(), None),
+    };
+    let arg = |i: usize| term(c.exprs.get(i)?, params);
+    let (terms, relation, rhs, arity) = match name {
+        "int_lin_eq" | "int_lin_ne" | "int_lin_le" => {
+            let coefficients = array(c.exprs.first()?, params)?;
+            let variables = array(c.exprs.get(1)?, params)?;
+            if coefficients.len() != variables.len() {
+                return None;
+            }
+            let mut terms = vec![];
+            for (a, x) in coefficients.into_iter().zip(variables) {
+                let Term::Const(a) = a else { return None };
+                terms.push((a, x));
+            }
+            let relation = match name {
+                "int_lin_eq" => Relation::Eq,
+                "int_lin_ne" => Relation::Ne,
+                _ => Relation::Le,
+            };
+            (terms, relation, arg(2)?, 3)
+        }
+        "int_eq" | "int_ne" | "int_le" | "int_lt" => {
+            let (relation, rhs) = match name {
+                "int_eq" => (Relation::Eq, 0),
+                "int_ne" => (Relation::Ne, 0),
+                "int_le" => (Relation::Le, 0),
+                _ => (Relation::Le, -1),
+            };
+            let terms = vec![(1, arg(0)?), (-1, arg(1)?)];
+            (terms, relation, Term::Const(rhs), 2)
+        }
+        "int_plus" if reified.is_none() => {
+            let terms = vec![(1, arg(0)?), (1, arg(1)?), (-1, arg(2)?)];
+            (terms, Relation::Eq, Term::Const(0), 3)
+        }
+        "int_times" if reified.is_none() => {
+            let factor = match (arg(0)?, arg(1)?) {
+                (Term::Const(a), x) | (x, Term::Const(a)) => (a, x),
+                _ => return None,
+            };
+            (vec![factor, (-1, arg(2)?)], Relation::Eq, Term::Const(0), 3)
+        }
+        _ => return None,
+    };
+    let reification = match reified {
+        None if c.exprs.len() == arity => Reification::None,
+        Some(equiv) if c.exprs.len() == arity + 1 => {
+            let literal = literal(&c.exprs[arity], params)?;
+            if equiv {
+                Reification::Equiv(literal)
+            } else {
+                Reification::Imp(literal)
+            }
+        }
+        _ => return None,
+    };
+    let Term::Const(mut rhs) = rhs else {
+        return None;
+    };
+    let mut merged: Vec<(i128, String)> = vec![];
+    for (a, x) in terms {
+        match x {
+            Term::Const(k) => rhs = rhs.checked_sub(a.checked_mul(k)?)?,
+            Term::Var(x) => match merged.iter_mut().find(|(_, y)| *y == x) {
+                Some((b, _)) => *b = b.checked_add(a)?,
+                None => merged.push((a, x)),
+            },
+        }
+    }
+    merged.retain(|(a, _)| *a != 0);
+    Some(Linear {
+        terms: merged,
+        relation,
+        rhs,
+        reification,
+    })
+}
+
+fn identifier(id: &str, params: &HashMap<String, ParDeclItem>) -> Option<Term> {
+    match params.get(id) {
+        Some(ParDeclItem::Int { int, .. }) => Some(Term::Const(*int)),
+        Some(_) => None,
+        None => Some(Term::Var(id.to_string())),
+    }
+}
+fn term(e: &Expr, params: &HashMap<String, ParDeclItem>) -> Option<Term> {
+    match e {
+        Expr::Int(i) => Some(Term::Const(*i)),
+        Expr::VarParIdentifier(id) => identifier(id, params),
+        _ => None,
+    }
+}
+/// Elements of an array literal or of an array parameter.
+///
+/// An array consisting only of identifiers is parsed as array of bools.
+fn array(e: &Expr, params: &HashMap<String, ParDeclItem>) -> Option<Vec<Term>> {
+    match e {
+        Expr::ArrayOfInt(v) => v
+            .iter()
+            .map(|e| match e {
+                IntExpr::Int(i) => Some(Term::Const(*i)),
+                IntExpr::VarParIdentifier(id) => identifier(id, params),
+            })
+            .collect(),
+        Expr::ArrayOfBool(v) => v
+            .iter()
+            .map(|e| match e {
+                BoolExpr::VarParIdentifier(id) => identifier(id, params),
+                BoolExpr::Bool(_) => None,
+            })
+            .collect(),
+        Expr::VarParIdentifier(id) => match params.get(id) {
+            Some(ParDeclItem::ArrayOfInt { v, .. }) => {
+                Some(v.iter().map(|i| Term::Const(*i)).collect())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+fn literal(e: &Expr, params: &HashMap<String, ParDeclItem>) -> Option<Literal> {
+    match e {
+        Expr::Bool(b) => Some(Literal::Const(*b)),
+        Expr::VarParIdentifier(id) => match params.get(id) {
+            Some(ParDeclItem::Bool { bool, .. }) => Some(Literal::Const(*bool)),
+            Some(_) => None,
+            None => Some(Literal::Var(id.clone())),
+        },
+        _ => None,
+    }
+}
+
+#[test]
+fn test_linear() {
+    use flatzinc::Stmt;
+    use std::str::FromStr;
+    let mut params = HashMap::new();
+    for p in ["array [1..2] of int: a = [2,-1];", "int: k = 3;"] {
+        let Ok(Stmt::Parameter(p)) = Stmt::from_str(p) else {
+            panic!("{p}")
+        };
+        params.insert(crate::par_decl_id(&p).to_string(), p);
+    }
+    let parse = |s: &str| match Stmt::from_str(s) {
+        Ok(Stmt::Constraint(c)) => linear(&c, &params),
+        _ => panic!("{s}"),
+    };
+    let lin = parse("constraint int_lin_le_reif(a,[x,y],k,r);").unwrap();
+    assert_eq!(lin.terms, vec![(2, "x".to_string()), (-1, "y".to_string())]);
+    assert_eq!((lin.relation, lin.rhs), (Relation::Le, 3));
+    assert_eq!(
+        lin.reification,
+        Reification::Equiv(Literal::Var("r".to_string()))
+    );
+    let lin = parse("constraint int_lt(x,5);").unwrap();
+    assert_eq!((lin.terms, lin.rhs), (vec![(1, "x".to_string())], 4));
+    let lin = parse("constraint int_times(x,k,z);").unwrap();
+    assert_eq!(lin.terms, vec![(3, "x".to_string()), (-1, "z".to_string())]);
+    let lin = parse("constraint int_plus(x,x,y);").unwrap();
+    assert_eq!(lin.terms, vec![(2, "x".to_string()), (-1, "y".to_string())]);
+    assert_eq!(parse("constraint int_times(x,y,z);"), None);
+    assert_eq!(parse("constraint int_lin_eq([x,y],[1,2],3);"), None);
+    let max = i128::MAX;
+    assert_eq!(
+        parse(&format!("constraint int_lin_le([{max}],[2],3);")),
+        None
+    );
+    assert_eq!(
+        parse(&format!("constraint int_lin_le([{max},1],[x,x],3);")),
+        None
+    );
+    assert_eq!(
+        parse("constraint int_eq_imp(x,y,true);")
+            .unwrap()
+            .reification,
+        Reification::Imp(Literal::Const(true))
+    );
+}
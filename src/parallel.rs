@@ -0,0 +1,180 @@
+//! Parallel translation of large models.
+//!
+//! The input is read in chunks of statements. The statements of a chunk are parsed in parallel,
+//! checked in order, which numbers the constraints, and then written in parallel into one
+//! buffer per batch of statements. The buffers are written in the original order, so the output
+//! is the same as the sequential one.
+use crate::{parse_stmt, Translator};
+use anyhow::Result;
+use flatzinc::Stmt;
+use rayon::prelude::*;
+use std::io::Write;
+
+/// Lines read at once.
+const CHUNK: usize = 1 << 14;
+/// Statements written into one buffer.
+const BATCH: usize = 256;
+
+/// Translates statements given with their line.
+pub(crate) fn translate<S: AsRef<str> + Sync>(
+    translator: &mut Translator,
+    mut stmts: impl Iterator<Item = Result<(usize, S)>>,
+    mut out: impl Write,
+) -> Result<()> {
+    loop {
+        let chunk: Vec<_> = stmts.by_ref().take(CHUNK).collect::<Result<_>>()?;
+        if chunk.is_empty() {
+            return Ok(());
+        }
+        let parsed: Vec<_> = chunk
+            .par_iter()
+            .map(|(_, stmt)| parse_stmt(stmt.as_ref()))
+            .collect();
+        let mut stmts = Vec::with_capacity(parsed.len());
+        let mut error = None;
+        for ((line, _), stmt) in chunk.iter().zip(parsed) {
+            match stmt {
+                Ok(stmt) => stmts.push((*line, stmt)),
+                Err(err) => {
+                    error = Some((*line, err));
+                    break;
+                }
+            }
+        }
+        write_stmts(translator, stmts, &mut out)?;
+        if let Some((line, err)) = error {
+            translator.line = line;
+            return Err(err);
+        }
+    }
+}
+
+/// Checks the statements in order and writes them in parallel.
+///
+/// Parameters are only known to the statements after them, so the statements checked so far
+/// are written before a statement following new parameters. If a statement fails, the
+/// statements before it are written.
+pub(crate) fn write_stmts(
+    translator: &mut Translator,
+    stmts: Vec<(usize, Stmt)>,
+    mut out: impl Write,
+) -> Result<()> {
+    let mut checked = Vec::with_capacity(stmts.len());
+    let mut new_params = false;
+    for (line, stmt) in stmts {
+        let is_param = matches!(stmt, Stmt::Parameter(_));
+        if new_params && !is_param {
+            write_checked(translator, &mut checked, &mut out)?;
+            new_params = false;
+        }
+        translator.line = line;
+        match translator.check_stmt(&stmt) {
+            Ok(i) => checked.push((stmt, i)),
+            Err(err) => {
+                write_checked(translator, &mut checked, &mut out)?;
+                return Err(err);
+            }
+        }
+        new_params |= is_param;
+    }
+    write_checked(translator, &mut checked, &mut out)
+}
+
+/// Writes checked statements in parallel and then keeps their parameters.
+fn write_checked(
+    translator: &mut Translator,
+    checked: &mut Vec<(Stmt, usize)>,
+    mut out: impl Write,
+) -> Result<()> {
+    let writer = &*translator;
+    let buffers: Vec<_> = checked
+        .par_chunks(BATCH)
+        .map(|batch| {
+            let mut buf = vec![];
+            for (stmt, i) in batch {
+                if let Err(err) = writer.write_checked(&mut buf, stmt, *i) {
+                    return (buf, Some(err));
+                }
+            }
+            (buf, None)
+        })
+        .collect();
+    for (buf, err) in buffers {
+        out.write_all(&buf)?;
+        if let Some(err) = err {
+            return Err(err);
+        }
+    }
+    for (stmt, _) in checked.drain(..) {
+        if let Stmt::Parameter(p) = stmt {
+            translator.keep_param(p);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_parallel() {
+    use crate::{Options, OutputMode};
+    let mut model = include_str!("../tests/corpus/test.fzn").to_string();
+    model = model.replace("solve satisfy;", "");
+    for i in 0..CHUNK + 100 {
+        model.push_str(&format!("constraint int_le(vi{},{i});\n", i % 3 + 1));
+    }
+    model.push_str("solve satisfy;\n");
+    for mode in [OutputMode::Facts, OutputMode::Clingcon] {
+        let translate = |parallel| {
+            let mut res = Vec::new();
+            let opts = Options {
+                mode,
+                parallel,
+                ..Options::default()
+            };
+            crate::translate(model.as_bytes(), &mut res, opts)
+                .unwrap()
+                .finish()
+                .unwrap();
+            res
+        };
+        assert!(translate(true) == translate(false));
+    }
+
+    // a parameter is unknown to the statements before it
+    let input = "array [1..2] of var int: y :: output_array([1..2]) = a;\n\
+                 array [1..2] of int: a = [1,2];\n\
+                 solve satisfy;\n";
+    let translate = |parallel| {
+        let mut res = Vec::new();
+        let opts = Options {
+            parallel,
+            ..Options::default()
+        };
+        crate::translate(input.as_bytes(), &mut res, opts)
+            .unwrap()
+            .finish()
+            .unwrap();
+        String::from_utf8(res).unwrap()
+    };
+    assert!(!translate(true).contains("output_element"));
+    assert_eq!(translate(true), translate(false));
+
+    let input = "var 1..3: x;\nconstraint int_le(x,1);\nvar 1..3: y;\n";
+    let mut res = Vec::new();
+    let opts = Options {
+        order: crate::StatementOrder::Strict,
+        parallel: true,
+        ..Options::default()
+    };
+    let err = crate::translate(input.as_bytes(), &mut res, opts).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Statements in wrong order: variable in line 3"
+    );
+    assert_eq!(
+        std::str::from_utf8(&res).unwrap(),
+        "variable_type(\"x\",int,range,(value,1,value,3)).\n\
+         constraint(c1,\"int_le\").\n\
+         constraint_value(c1,0,var,\"x\").\n\
+         constraint_value(c1,1,value,1).\n"
+    );
+}
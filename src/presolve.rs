@@ -0,0 +1,437 @@
+//! Presolving of unary constraints into the domains of the variables.
+//!
+//! Constraints `int_le`, `int_lt`, `int_eq` and `set_in` between an integer variable and a
+//! constant, and `bool_eq` between a Boolean variable and a constant, are moved into the
+//! declarations of their variables. The constraints of a variable are only removed if its
+//! resulting domain can be declared, i.e. it is bounded on both sides and not empty.
+use crate::par_decl_id;
+use flatzinc::{
+    BoolExpr, ConstraintItem, Expr, IntExpr, ParDeclItem, SetLiteral, SetLiteralExpr, Stmt,
+    VarDeclItem,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+/// What the presolve pass changed.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Variables with a tightened domain
+    pub tightened: Vec<String>,
+    /// Variables fixed to a single value
+    pub fixed: Vec<String>,
+    /// Variables with an empty domain, their constraints are kept
+    pub empty: Vec<String>,
+    /// Number of removed constraints
+    pub removed: usize,
+}
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Presolve removed {} constraint(s)", self.removed)?;
+        for (what, ids) in [
+            ("tightened", &self.tightened),
+            ("fixed", &self.fixed),
+            ("empty domain", &self.empty),
+        ] {
+            if !ids.is_empty() {
+                write!(f, "\n  {what}: {}", ids.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Restriction of the domain of a variable by a unary constraint.
+enum Restriction {
+    Le(i128),
+    Ge(i128),
+    In(Vec<i128>),
+    Is(bool),
+}
+
+/// Domain of a variable collected from its declaration and its unary constraints.
+#[derive(Default)]
+pub(crate) struct Domain {
+    lb: Option<i128>,
+    ub: Option<i128>,
+    set: Option<Vec<i128>>,
+    value: Option<bool>,
+    conflict: bool,
+    constraints: Vec<usize>,
+}
+impl Domain {
+    /// Intersects the domain with the domain of another variable.
+    pub(crate) fn merge(&mut self, other: Domain) {
+        let restrictions = [
+            other.lb.map(Restriction::Ge),
+            other.ub.map(Restriction::Le),
+            other.set.map(Restriction::In),
+            other.value.map(Restriction::Is),
+        ];
+        for r in restrictions.into_iter().flatten() {
+            self.restrict(r);
+        }
+        self.conflict |= other.conflict;
+    }
+    fn restrict(&mut self, r: Restriction) {
+        match r {
+            Restriction::Le(k) => self.ub = Some(self.ub.map_or(k, |ub| ub.min(k))),
+            Restriction::Ge(k) => self.lb = Some(self.lb.map_or(k, |lb| lb.max(k))),
+            Restriction::In(mut v) => {
+                if let Some(set) = &self.set {
+                    v.retain(|i| set.contains(i));
+                }
+                v.sort_unstable();
+                v.dedup();
+                self.set = Some(v);
+            }
+            Restriction::Is(b) => {
+                self.conflict |= self.value.is_some_and(|value| value != b);
+                self.value = Some(b);
+            }
+        }
+    }
+    /// The values of a domain restricted by a set.
+    fn values(&self) -> Option<Vec<i128>> {
+        let in_bounds =
+            |i: &i128| self.lb.is_none_or(|lb| lb <= *i) && self.ub.is_none_or(|ub| *i <= ub);
+        Some(
+            self.set
+                .as_ref()?
+                .iter()
+                .copied()
+                .filter(in_bounds)
+                .collect(),
+        )
+    }
+}
+
+/// Moves unary constraints into the declarations of their variables.
+pub fn presolve(stmts: &mut Vec<(usize, Stmt)>) -> Report {
+    let mut params = HashMap::new();
+    let mut domains: HashMap<String, Domain> = HashMap::new();
+    for (_, stmt) in stmts.iter() {
+        match stmt {
+            Stmt::Parameter(p) => {
+                params.insert(par_decl_id(p).to_string(), p.clone());
+            }
+            Stmt::Variable(d) => {
+                if let Some((id, domain)) = declared_domain(d, &params) {
+                    domains.insert(id.clone(), domain);
+                }
+            }
+            _ => {}
+        }
+    }
+    for (i, (_, stmt)) in stmts.iter().enumerate() {
+        if let Stmt::Constraint(c) = stmt {
+            if let Some((id, restrictions)) = unary(c, &params, &domains) {
+                let domain = domains.get_mut(&id).unwrap();
+                for r in restrictions {
+                    domain.restrict(r);
+                }
+                domain.constraints.push(i);
+            }
+        }
+    }
+    let mut report = Report::default();
+    let mut removed = HashSet::new();
+    for (_, stmt) in stmts.iter_mut() {
+        let Stmt::Variable(d) = stmt else { continue };
+        let Some(domain) = domains.get(crate::var_decl_id(d)) else {
+            continue;
+        };
+        if domain.constraints.is_empty() {
+            continue;
+        }
+        let Some((decl, fixed)) = tightened(d, domain) else {
+            if is_empty(d, domain) {
+                report.empty.push(crate::var_decl_id(d).to_string());
+            }
+            continue;
+        };
+        let id = crate::var_decl_id(d).to_string();
+        if fixed {
+            report.fixed.push(id);
+        } else {
+            report.tightened.push(id);
+        }
+        removed.extend(domain.constraints.iter().copied());
+        *d = decl;
+    }
+    report.removed = removed.len();
+    let mut i = 0;
+    stmts.retain(|_| {
+        i += 1;
+        !removed.contains(&(i - 1))
+    });
+    report
+}
+
+/// The domain of a declaration, including a value assigned as literal or parameter.
+pub(crate) fn declared_domain<'a>(
+    d: &'a VarDeclItem,
+    params: &HashMap<String, ParDeclItem>,
+) -> Option<(&'a String, Domain)> {
+    let mut domain = Domain::default();
+    let id = match d {
+        VarDeclItem::Int { id, .. } => id,
+        VarDeclItem::IntInRange { id, lb, ub, .. } => {
+            domain.lb = Some(*lb);
+            domain.ub = Some(*ub);
+            id
+        }
+        VarDeclItem::IntInSet { id, set, .. } => {
+            domain.restrict(Restriction::In(set.clone()));
+            id
+        }
+        VarDeclItem::Bool { id, expr, .. } => {
+            match expr {
+                Some(BoolExpr::Bool(b)) => domain.value = Some(*b),
+                Some(BoolExpr::VarParIdentifier(p)) => {
+                    if let Some(ParDeclItem::Bool { bool, .. }) = params.get(p) {
+                        domain.value = Some(*bool);
+                    }
+                }
+                None => {}
+            }
+            id
+        }
+        _ => return None,
+    };
+    if let VarDeclItem::Int { expr, .. }
+    | VarDeclItem::IntInRange { expr, .. }
+    | VarDeclItem::IntInSet { expr, .. } = d
+    {
+        match expr {
+            Some(IntExpr::Int(i)) => domain.restrict(Restriction::In(vec![*i])),
+            Some(IntExpr::VarParIdentifier(p)) => {
+                if let Some(ParDeclItem::Int { int, .. }) = params.get(p) {
+                    domain.restrict(Restriction::In(vec![*int]));
+                }
+            }
+            None => {}
+        }
+    }
+    Some((id, domain))
+}
+
+/// The declaration with the presolved domain, and whether the variable is fixed.
+pub(crate) fn tightened(d: &VarDeclItem, domain: &Domain) -> Option<(VarDeclItem, bool)> {
+    if let VarDeclItem::Bool { id, expr, annos } = d {
+        // an alias has no domain that could hold the value
+        if domain.conflict || matches!(expr, Some(BoolExpr::VarParIdentifier(_))) {
+            return None;
+        }
+        let decl = VarDeclItem::Bool {
+            id: id.clone(),
+            expr: expr.clone().or(domain.value.map(BoolExpr::Bool)),
+            annos: annos.clone(),
+        };
+        return Some((decl, true));
+    }
+    let (id, expr, annos) = match d {
+        VarDeclItem::Int { id, expr, annos }
+        | VarDeclItem::IntInRange {
+            id, expr, annos, ..
+        }
+        | VarDeclItem::IntInSet {
+            id, expr, annos, ..
+        } => (id.clone(), expr.clone(), annos.clone()),
+        _ => return None,
+    };
+    let (lb, ub, set) = match domain.values() {
+        Some(values) if values.is_empty() => return None,
+        Some(values) => {
+            let (lb, ub) = (values[0], values[values.len() - 1]);
+            (
+                lb,
+                ub,
+                (ub - lb + 1 != values.len() as i128).then_some(values),
+            )
+        }
+        None => (domain.lb?, domain.ub?, None),
+    };
+    if lb > ub {
+        return None;
+    }
+    let fixed = lb == ub;
+    let expr = match expr {
+        None if fixed => Some(IntExpr::Int(lb)),
+        expr => expr,
+    };
+    let decl = match set {
+        Some(set) => VarDeclItem::IntInSet {
+            id,
+            set,
+            expr,
+            annos,
+        },
+        None => VarDeclItem::IntInRange {
+            id,
+            lb,
+            ub,
+            expr,
+            annos,
+        },
+    };
+    Some((decl, fixed))
+}
+
+pub(crate) fn is_empty(d: &VarDeclItem, domain: &Domain) -> bool {
+    match d {
+        VarDeclItem::Bool { .. } => domain.conflict,
+        _ => match domain.values() {
+            Some(values) => values.is_empty(),
+            None => domain.lb.zip(domain.ub).is_some_and(|(lb, ub)| lb > ub),
+        },
+    }
+}
+
+/// The variable and the restrictions of a unary constraint.
+fn unary(
+    c: &ConstraintItem,
+    params: &HashMap<String, ParDeclItem>,
+    domains: &HashMap<String, Domain>,
+) -> Option<(String, Vec<Restriction>)> {
+    let var = |e: &Expr| match e {
+        Expr::VarParIdentifier(id) if domains.contains_key(id) => Some(id.clone()),
+        _ => None,
+    };
+    let int = |e: &Expr| match e {
+        Expr::Int(i) => Some(*i),
+        Expr::VarParIdentifier(id) => match params.get(id) {
+            Some(ParDeclItem::Int { int, .. }) => Some(*int),
+            _ => None,
+        },
+        _ => None,
+    };
+    let bool = |e: &Expr| match e {
+        Expr::Bool(b) => Some(*b),
+        Expr::VarParIdentifier(id) => match params.get(id) {
+            Some(ParDeclItem::Bool { bool, .. }) => Some(*bool),
+            _ => None,
+        },
+        _ => None,
+    };
+    let ordered = |a: &Expr, b: &Expr, offset: i128| {
+        if let (Some(x), Some(k)) = (var(a), int(b)) {
+            Some((x, vec![Restriction::Le(k - offset)]))
+        } else if let (Some(k), Some(x)) = (int(a), var(b)) {
+            Some((x, vec![Restriction::Ge(k + offset)]))
+        } else {
+            None
+        }
+    };
+    let (x, restrictions) = match (c.id.as_str(), c.exprs.as_slice()) {
+        ("int_le", [a, b]) => ordered(a, b, 0)?,
+        ("int_lt", [a, b]) => ordered(a, b, 1)?,
+        ("int_eq", [a, b]) => {
+            let (x, k) = match (var(a), int(b)) {
+                (Some(x), Some(k)) => (x, k),
+                _ => (var(b)?, int(a)?),
+            };
+            (x, vec![Restriction::Ge(k), Restriction::Le(k)])
+        }
+        ("set_in", [a, s]) => {
+            let restrictions = match s {
+                Expr::Set(SetLiteralExpr::IntInRange(IntExpr::Int(lb), IntExpr::Int(ub))) => {
+                    vec![Restriction::Ge(*lb), Restriction::Le(*ub)]
+                }
+                Expr::Set(SetLiteralExpr::SetInts(v)) => vec![Restriction::In(
+                    v.iter()
+                        .map(|e| match e {
+                            IntExpr::Int(i) => Some(*i),
+                            IntExpr::VarParIdentifier(_) => None,
+                        })
+                        .collect::<Option<_>>()?,
+                )],
+                Expr::VarParIdentifier(id) => match params.get(id) {
+                    Some(ParDeclItem::SetOfInt { set_literal, .. }) => match set_literal {
+                        SetLiteral::IntRange(lb, ub) => {
+                            vec![Restriction::Ge(*lb), Restriction::Le(*ub)]
+                        }
+                        SetLiteral::SetInts(v) => vec![Restriction::In(v.clone())],
+                        _ => return None,
+                    },
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            (var(a)?, restrictions)
+        }
+        ("bool_eq", [a, b]) => {
+            let (x, value) = match (var(a), bool(b)) {
+                (Some(x), Some(value)) => (x, value),
+                _ => (var(b)?, bool(a)?),
+            };
+            (x, vec![Restriction::Is(value)])
+        }
+        _ => return None,
+    };
+    Some((x, restrictions))
+}
+
+#[test]
+fn test_presolve() {
+    use std::str::FromStr;
+    let input = [
+        "int: k = 10;",
+        "var int: x;",
+        "var 1..20: y :: output_var;",
+        "var int: z;",
+        "var {1,3,5,7}: w;",
+        "var bool: b;",
+        "var 1..3: e;",
+        "constraint int_le(x,k);",
+        "constraint int_lt(0,x);",
+        "constraint int_le(y,x);",
+        "constraint int_le(3,y);",
+        "constraint int_lt(y,6);",
+        "constraint int_le(z,5);",
+        "constraint set_in(w,{3,4,5,6,7});",
+        "constraint bool_eq(true,b);",
+        "constraint int_eq(e,4);",
+        "constraint int_eq(y,4);",
+        "solve satisfy;",
+    ];
+    let mut stmts: Vec<_> = input
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (i + 1, Stmt::from_str(s).unwrap()))
+        .collect();
+    let report = presolve(&mut stmts);
+    assert_eq!(
+        report,
+        Report {
+            tightened: vec!["x".to_string(), "w".to_string()],
+            fixed: vec!["y".to_string(), "b".to_string()],
+            empty: vec!["e".to_string()],
+            removed: 7,
+        }
+    );
+    let expected = [
+        "int: k = 10;",
+        "var 1..10: x;",
+        "var 4..4: y :: output_var = 4;",
+        "var int: z;",
+        "var {3,5,7}: w;",
+        "var bool: b = true;",
+        "var 1..3: e;",
+        "constraint int_le(y,x);",
+        "constraint int_le(z,5);",
+        "constraint int_eq(e,4);",
+        "solve satisfy;",
+    ];
+    let expected: Vec<_> = expected
+        .iter()
+        .map(|s| Stmt::from_str(s).unwrap())
+        .collect();
+    let stmts: Vec<_> = stmts.into_iter().map(|(_, s)| s).collect();
+    assert_eq!(stmts, expected);
+    assert_eq!(
+        report.to_string(),
+        "Presolve removed 7 constraint(s)\n  tightened: x, w\n  fixed: y, b\n  empty domain: e"
+    );
+}
@@ -0,0 +1,650 @@
+//! Reconstruction of a FlatZinc model from the facts of the translation.
+//!
+//! Facts of other predicates, like the theory atoms of the clingcon or clingo-dl output, are
+//! ignored. Some information is not contained in the facts: empty arrays have no facts, so an
+//! array argument of a constraint without facts becomes `[]`, and predicate parameters are
+//! written without `var`. Floats are written as strings, a string holding an integer outside of
+//! clingo's range is read as integer. Identifiers may be strings or constants.
+use crate::{
+    facts::{parse_facts, Fact, Term},
+    fits_clingo_int, FlatZincError,
+};
+use anyhow::Result;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    io::{BufRead, Write},
+};
+
+/// Most consecutive constraint arguments without facts, which are read as empty arrays.
+const MAX_GAP: i128 = 1 << 10;
+
+/// Reads facts and writes the FlatZinc model they describe, one statement per line.
+pub fn reverse(mut input: impl BufRead, mut out: impl Write) -> Result<()> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    let model = Model::new(&parse_facts(&text)?)?;
+    model.write(&mut out)
+}
+
+/// Entities in the order of their first fact.
+struct Table<T> {
+    order: Vec<String>,
+    items: HashMap<String, T>,
+}
+impl<T> Default for Table<T> {
+    fn default() -> Self {
+        Table {
+            order: vec![],
+            items: HashMap::new(),
+        }
+    }
+}
+impl<T: Default> Table<T> {
+    fn get(&mut self, id: &str) -> &mut T {
+        if !self.items.contains_key(id) {
+            self.order.push(id.to_string());
+        }
+        self.items.entry(id.to_string()).or_default()
+    }
+    fn iter(&self) -> impl Iterator<Item = (&String, &T)> {
+        self.order.iter().map(|id| (id, &self.items[id]))
+    }
+}
+
+/// The arguments of a fact following the identifier.
+type Args = Vec<Term>;
+
+#[derive(Default)]
+struct Var {
+    types: Vec<Args>,
+    values: Vec<Args>,
+    output_var: bool,
+    output_array: BTreeMap<i128, (i128, i128)>,
+}
+
+#[derive(Default)]
+struct Param {
+    types: Vec<Args>,
+    values: Vec<Args>,
+}
+
+#[derive(Default)]
+struct Constraint {
+    name: Option<String>,
+    args: BTreeMap<i128, Vec<Args>>,
+}
+
+#[derive(Default)]
+struct Model {
+    predicates: Table<BTreeMap<i128, (String, Vec<Args>)>>,
+    params: Table<Param>,
+    vars: Table<Var>,
+    constraints: Table<Constraint>,
+    solve: Vec<Args>,
+}
+impl Model {
+    fn new(facts: &[Fact]) -> Result<Model> {
+        let mut model = Model::default();
+        for fact in facts {
+            let args = identifiers(fact);
+            let args = args.as_slice();
+            match (fact.name.as_str(), args) {
+                ("predicate", [Term::Str(p)]) => {
+                    model.predicates.get(p);
+                }
+                (
+                    "predicate_parameter",
+                    [Term::Str(p), Term::Int(pos), Term::Str(id), rest @ ..],
+                ) => {
+                    let param = model.predicates.get(p).entry(*pos);
+                    param
+                        .or_insert_with(|| (id.clone(), vec![]))
+                        .1
+                        .push(rest.to_vec());
+                }
+                ("parameter_type", [Term::Str(id), rest @ ..]) => {
+                    model.params.get(id).types.push(rest.to_vec());
+                }
+                ("parameter_value", [Term::Str(id), rest @ ..]) => {
+                    model.params.get(id).values.push(rest.to_vec());
+                }
+                ("variable_type", [Term::Str(id), rest @ ..]) => {
+                    model.vars.get(id).types.push(rest.to_vec());
+                }
+                ("variable_value", [Term::Str(id), rest @ ..]) => {
+                    model.vars.get(id).values.push(rest.to_vec());
+                }
+                ("output_var", [Term::Str(id)]) => model.vars.get(id).output_var = true,
+                ("output_array", [Term::Str(id), Term::Int(pos), Term::Tuple(bounds)]) => {
+                    let [Term::Int(lb), Term::Int(ub)] = bounds.as_slice() else {
+                        return Err(invalid_fact(fact));
+                    };
+                    model.vars.get(id).output_array.insert(*pos, (*lb, *ub));
+                }
+                ("constraint", [c, Term::Str(name)]) => {
+                    model.constraints.get(&c.to_string()).name = Some(name.clone());
+                }
+                ("constraint_value", [c, Term::Int(pos), rest @ ..]) => {
+                    let constraint = model.constraints.get(&c.to_string());
+                    constraint.args.entry(*pos).or_default().push(rest.to_vec());
+                }
+                ("solve", rest) => model.solve.push(rest.to_vec()),
+                (
+                    "predicate"
+                    | "predicate_parameter"
+                    | "parameter_type"
+                    | "parameter_value"
+                    | "variable_type"
+                    | "variable_value"
+                    | "output_var"
+                    | "output_array"
+                    | "constraint"
+                    | "constraint_value",
+                    _,
+                ) => return Err(invalid_fact(fact)),
+                _ => {}
+            }
+        }
+        Ok(model)
+    }
+    fn write(&self, mut out: impl Write) -> Result<()> {
+        for (id, params) in self.predicates.iter() {
+            let mut decls = vec![];
+            for (i, (pos, (name, types))) in params.iter().enumerate() {
+                if *pos != i as i128 {
+                    return Err(invalid(id, format!("missing parameter {i}")));
+                }
+                let t = pred_par_type(&refs(types)).map_err(|msg| invalid(id, msg))?;
+                decls.push(format!("{t}: {name}"));
+            }
+            writeln!(out, "predicate {id}({});", decls.join(", "))?;
+        }
+        for (id, param) in self.params.iter() {
+            let (t, value) = par_decl(param).map_err(|msg| invalid(id, msg))?;
+            writeln!(out, "{t}: {id} = {value};")?;
+        }
+        for (id, var) in self.vars.iter() {
+            writeln!(
+                out,
+                "{};",
+                var_decl(id, var).map_err(|msg| invalid(id, msg))?
+            )?;
+        }
+        for (id, c) in self.constraints.iter() {
+            let Some(name) = &c.name else {
+                return Err(invalid(id, "missing constraint fact".to_string()));
+            };
+            let mut args = vec![];
+            for (pos, values) in c.args.iter() {
+                // positions without facts are empty arrays
+                let gap = pos - args.len() as i128;
+                if !(0..=MAX_GAP).contains(&gap) {
+                    return Err(invalid(id, format!("invalid argument position {pos}")));
+                }
+                args.extend((0..gap).map(|_| Value::Array(vec![]).to_string()));
+                let arg = expr(&refs(values)).map_err(|msg| invalid(id, msg))?;
+                args.push(arg.to_string());
+            }
+            writeln!(out, "constraint {name}({});", args.join(", "))?;
+        }
+        writeln!(
+            out,
+            "{};",
+            solve(&refs(&self.solve)).map_err(|msg| invalid("solve", msg))?
+        )?;
+        Ok(())
+    }
+}
+
+fn invalid(id: &str, msg: String) -> anyhow::Error {
+    FlatZincError::InvalidFacts {
+        id: id.to_string(),
+        msg,
+    }
+    .into()
+}
+fn invalid_fact(fact: &Fact) -> anyhow::Error {
+    FlatZincError::InvalidFact {
+        line: fact.line,
+        msg: format!("unexpected arguments of {}", fact.name),
+    }
+    .into()
+}
+fn refs(args: &[Args]) -> Vec<&[Term]> {
+    args.iter().map(Vec::as_slice).collect()
+}
+
+/// A FlatZinc expression.
+enum Value {
+    Bool(bool),
+    Int(String),
+    Float(String),
+    Id(String),
+    Set(String),
+    Array(Vec<Value>),
+}
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Int(s) | Value::Float(s) | Value::Id(s) | Value::Set(s) => write!(f, "{s}"),
+            Value::Array(v) => {
+                let v: Vec<_> = v.iter().map(Value::to_string).collect();
+                write!(f, "[{}]", v.join(", "))
+            }
+        }
+    }
+}
+
+/// The expression described by the arguments of the facts of one value, like the `array`
+/// facts of the elements of an array or the `set` facts of the elements of a set.
+fn expr(values: &[&[Term]]) -> Result<Value, String> {
+    let kind = |args: &[Term]| args.first().and_then(Term::constant).map(str::to_string);
+    // the size of a constant set accompanies its elements or other terms
+    let (sizes, values): (Vec<&[Term]>, Vec<_>) = values
+        .iter()
+        .partition(|args| kind(args).as_deref() == Some("set_size"));
+    match (sizes.as_slice(), values.is_empty()) {
+        ([], _) | ([_], false) => {}
+        ([[_, Term::Int(0)]], true) => return Ok(Value::Set("{}".to_string())),
+        _ => return Err("invalid set size".to_string()),
+    }
+    let values = values.as_slice();
+    let Some(first) = values.first() else {
+        return Err("missing value".to_string());
+    };
+    let first = kind(first);
+    if values.iter().any(|args| kind(args) != first) {
+        return Err("conflicting values".to_string());
+    }
+    match (first.as_deref(), values) {
+        (Some("array"), _) => {
+            let mut elements: BTreeMap<i128, Vec<&[Term]>> = BTreeMap::new();
+            for args in values {
+                let [_, Term::Tuple(element)] = args else {
+                    return Err(format!("invalid array element {}", args[1]));
+                };
+                let [Term::Int(pos), rest @ ..] = element.as_slice() else {
+                    return Err(format!("invalid array element {}", args[1]));
+                };
+                elements.entry(*pos).or_default().push(rest);
+            }
+            let mut array = vec![];
+            for (i, (pos, element)) in elements.iter().enumerate() {
+                if *pos != i as i128 {
+                    return Err(format!("missing array element {i}"));
+                }
+                array.push(expr(element)?);
+            }
+            Ok(Value::Array(array))
+        }
+        (Some("elem"), _) => {
+            let mut elements = vec![];
+            for args in values {
+                let [_, v] = args else {
+                    return Err("invalid set element".to_string());
+                };
+                elements.push(literal(v)?.to_string());
+            }
+            Ok(Value::Set(format!("{{{}}}", elements.join(","))))
+        }
+        (Some("set"), _) => {
+            let mut elements = vec![];
+            for args in values {
+                let [_, Term::Tuple(element)] = args else {
+                    return Err("invalid set element".to_string());
+                };
+                let [k, v] = element.as_slice() else {
+                    return Err("invalid set element".to_string());
+                };
+                elements.push(scalar(k, v)?.to_string());
+            }
+            Ok(Value::Set(format!("{{{}}}", elements.join(","))))
+        }
+        (_, [args]) => match args {
+            [k, Term::Tuple(bounds)] if matches!(k.constant(), Some("range" | "bounds")) => {
+                let [k1, lb, k2, ub] = bounds.as_slice() else {
+                    return Err(format!("invalid bounds {}", args[1]));
+                };
+                Ok(Value::Set(format!(
+                    "{}..{}",
+                    scalar(k1, lb)?,
+                    scalar(k2, ub)?
+                )))
+            }
+            [k, v] => scalar(k, v),
+            [k] if k.constant() == Some("empty_set") => Ok(Value::Set("{}".to_string())),
+            _ => Err(format!("unexpected value {}", Term::Tuple(args.to_vec()))),
+        },
+        _ => Err("conflicting values".to_string()),
+    }
+}
+/// The arguments of a fact with the identifiers of variables and parameters as strings.
+fn identifiers(fact: &Fact) -> Vec<Term> {
+    let pos = match fact.name.as_str() {
+        "predicate_parameter" => 2,
+        "parameter_type" | "parameter_value" | "variable_type" | "variable_value"
+        | "output_var" | "output_array" => 0,
+        _ => return fact.args.clone(),
+    };
+    let mut args = fact.args.clone();
+    if let Some(id) = args.get_mut(pos) {
+        if let Some(name) = id.constant() {
+            *id = Term::Str(name.to_string());
+        }
+    }
+    args
+}
+/// A literal `value,V` or an identifier `var,"X"`.
+fn scalar(kind: &Term, v: &Term) -> Result<Value, String> {
+    match (kind.constant(), v) {
+        (Some("value"), v) => literal(v),
+        (Some("var"), id) => match id.ident() {
+            Some(id) => Ok(Value::Id(id.to_string())),
+            None => Err(format!("unexpected identifier {id}")),
+        },
+        _ => Err(format!("unexpected value {kind},{v}")),
+    }
+}
+fn literal(v: &Term) -> Result<Value, String> {
+    match v {
+        Term::Int(i) => Ok(Value::Int(i.to_string())),
+        Term::Str(s) => match s.parse::<i128>() {
+            Ok(i) if !fits_clingo_int(i) => Ok(Value::Int(s.clone())),
+            _ => Ok(Value::Float(float_literal(s))),
+        },
+        t => match t.constant() {
+            Some("true") => Ok(Value::Bool(true)),
+            Some("false") => Ok(Value::Bool(false)),
+            _ => Err(format!("unexpected literal {t}")),
+        },
+    }
+}
+/// Adds the fraction that Rust omits when formatting whole floats.
+fn float_literal(s: &str) -> String {
+    if s.contains(['.', 'e', 'E']) || !s.contains(|c: char| c.is_ascii_digit()) {
+        s.to_string()
+    } else {
+        format!("{s}.0")
+    }
+}
+
+/// The type and value of a parameter, with the type of the `parameter_type` fact if there is one.
+fn par_decl(param: &Param) -> Result<(String, Value), String> {
+    let declared = match refs(&param.types).as_slice() {
+        [] => None,
+        [t] => Some(declared_par_type(t)?),
+        _ => return Err("conflicting types".to_string()),
+    };
+    let value = match &declared {
+        // empty arrays have no values
+        Some(t) if t.starts_with("array") && param.values.is_empty() => Value::Array(vec![]),
+        _ => expr(&refs(&param.values))?,
+    };
+    let t = match declared {
+        Some(t) => t,
+        None => par_type(&value)?,
+    };
+    Ok((t, value))
+}
+/// The type `bool`, `int`, `float`, `set_of_int` or `array(N,T)` of a `parameter_type` fact.
+fn declared_par_type(t: &[Term]) -> Result<String, String> {
+    let basic = |t: &Term| match t.constant() {
+        Some("bool") => Ok("bool"),
+        Some("int") => Ok("int"),
+        Some("float") => Ok("float"),
+        Some("set_of_int") => Ok("set of int"),
+        _ => Err(format!("unexpected parameter type {t}")),
+    };
+    match t {
+        [Term::Func(array, args)] if array == "array" => match args.as_slice() {
+            [Term::Int(n), element] => Ok(format!("array [1..{n}] of {}", basic(element)?)),
+            _ => Err(format!("invalid array type {}", t[0])),
+        },
+        [t] => basic(t).map(str::to_string),
+        _ => Err(format!("unexpected type {}", Term::Tuple(t.to_vec()))),
+    }
+}
+fn par_type(value: &Value) -> Result<String, String> {
+    let basic = |value: &Value| match value {
+        Value::Bool(_) => Ok("bool"),
+        Value::Int(_) => Ok("int"),
+        Value::Float(_) => Ok("float"),
+        Value::Set(_) => Ok("set of int"),
+        Value::Id(id) => Err(format!("unexpected identifier {id}")),
+        Value::Array(_) => Err("unexpected nested array".to_string()),
+    };
+    match value {
+        Value::Array(v) => {
+            let mut t = None;
+            for e in v {
+                match (t, basic(e)?) {
+                    (None, e) | (Some("int"), e @ "float") => t = Some(e),
+                    (Some("float"), "int") => {}
+                    (Some(t), e) if t != e => return Err("mixed array".to_string()),
+                    _ => {}
+                }
+            }
+            Ok(format!("array [1..{}] of {}", v.len(), t.unwrap_or("int")))
+        }
+        value => basic(value).map(str::to_string),
+    }
+}
+fn var_decl(id: &str, var: &Var) -> Result<String, String> {
+    let types = refs(&var.types);
+    let (mut decl, array) = match types.first() {
+        None => return Err("missing variable_type".to_string()),
+        Some([Term::Func(array, args), ..]) if array == "array" => {
+            let Some(Term::Int(n)) = args.first() else {
+                return Err(format!("invalid array type {}", types[0][0]));
+            };
+            let elements = element_types(&types)?;
+            (
+                format!("array [1..{n}] of var {}: {id}", basic_type(&elements)?),
+                true,
+            )
+        }
+        Some(_) => (format!("var {}: {id}", basic_type(&types)?), false),
+    };
+    if var.output_var {
+        decl.push_str(" :: output_var");
+    }
+    if !var.output_array.is_empty() {
+        let index_sets: Vec<_> = var
+            .output_array
+            .values()
+            .map(|(lb, ub)| format!("{lb}..{ub}"))
+            .collect();
+        decl.push_str(&format!(" :: output_array([{}])", index_sets.join(", ")));
+    }
+    let value = match refs(&var.values).as_slice() {
+        [] => return Ok(decl),
+        [[k, id]] if array && k.constant() == Some("value") && id.ident().is_some() => {
+            Value::Id(id.ident().unwrap_or_default().to_string())
+        }
+        values => expr(values)?,
+    };
+    Ok(format!("{decl} = {value}"))
+}
+/// The element types of `array(N,T)` types.
+fn element_types<'a>(types: &[&'a [Term]]) -> Result<Vec<&'a [Term]>, String> {
+    types
+        .iter()
+        .map(|t| match t {
+            [Term::Func(array, args)] if array == "array" && args.len() > 1 => Ok(&args[1..]),
+            t => Err(format!("unexpected array type {}", Term::Tuple(t.to_vec()))),
+        })
+        .collect()
+}
+/// The type of the facts `int`, `int,range,(..)`, `int,set,(..)`, `float,bounds,(..)`,
+/// `float,set,(..)` and `set_of_int,..`.
+fn basic_type(types: &[&[Term]]) -> Result<String, String> {
+    let Some(first) = types.first().and_then(|t| t.first()) else {
+        return Err("missing type".to_string());
+    };
+    let kind = first.constant().unwrap_or_default();
+    let tails: Vec<_> = types
+        .iter()
+        .map(|t| match t.first() {
+            Some(h) if h.constant() == Some(kind) => Ok(&t[1..]),
+            _ => Err("conflicting types".to_string()),
+        })
+        .collect::<Result<_, _>>()?;
+    match (kind, tails.as_slice()) {
+        ("bool" | "int" | "float", [[]]) => Ok(kind.to_string()),
+        ("set_of_int" | "set", [[]]) => Ok("set of int".to_string()),
+        ("int" | "float", tails) => match expr(tails)? {
+            Value::Set(s) => Ok(s),
+            _ => Err(format!("invalid {kind} domain")),
+        },
+        ("set_of_int", tails) => match expr(tails)? {
+            Value::Set(s) => Ok(format!("set of {s}")),
+            _ => Err("invalid set domain".to_string()),
+        },
+        _ => Err(format!("unexpected type {first}")),
+    }
+}
+fn pred_par_type(types: &[&[Term]]) -> Result<String, String> {
+    match types.first() {
+        Some([Term::Func(array, args), ..]) if array == "array" => {
+            let ix = match args.first() {
+                Some(Term::Int(n)) => format!("1..{n}"),
+                Some(t) if t.constant() == Some("int") => "int".to_string(),
+                _ => return Err("invalid array index".to_string()),
+            };
+            Ok(format!(
+                "array [{ix}] of {}",
+                basic_type(&element_types(types)?)?
+            ))
+        }
+        _ => basic_type(types),
+    }
+}
+fn solve(goals: &[&[Term]]) -> Result<String, String> {
+    let Some(first) = goals.first() else {
+        return Err("missing solve fact".to_string());
+    };
+    match first.first().and_then(Term::constant) {
+        Some("satisfy") if goals.len() == 1 && first.len() == 1 => Ok("solve satisfy".to_string()),
+        Some(ot @ ("minimize" | "maximize")) => {
+            let mut tails = vec![];
+            for goal in goals {
+                match goal.split_first() {
+                    Some((h, tail)) if h.constant() == Some(ot) => tails.push(tail),
+                    _ => return Err("conflicting solve facts".to_string()),
+                }
+            }
+            Ok(format!("solve {ot} {}", expr(&tails)?))
+        }
+        _ => Err("invalid solve fact".to_string()),
+    }
+}
+
+#[test]
+fn test_reverse() {
+    use crate::{translate, Options, StatementOrder};
+    let opts = || Options {
+        order: StatementOrder::Lenient,
+        ..Options::default()
+    };
+    let forward = |fzn: &str| {
+        let mut lp = vec![];
+        translate(fzn.as_bytes(), &mut lp, opts()).unwrap();
+        String::from_utf8(lp).unwrap()
+    };
+    let backward = |lp: &str| {
+        let mut fzn = vec![];
+        reverse(lp.as_bytes(), &mut fzn).unwrap();
+        String::from_utf8(fzn).unwrap()
+    };
+    let input = "predicate p(array [int] of var int: a, 1..3: b, {1.5,2.5}: c);\n\
+                 bool: t = true;\n\
+                 float: f = 1.0;\n\
+                 set of int: s = 1..3;\n\
+                 array [1..3] of int: a = [1,-2,3];\n\
+                 array [1..2] of set of int: ss = [{1,3},{}];\n\
+                 array [1..2] of float: fs = [1.0,2.5];\n\
+                 array [1..0] of int: n = [];\n\
+                 var 1..5: x :: output_var;\n\
+                 var {1,3}: y = x;\n\
+                 var 0.5..1.5: z;\n\
+                 var set of 1..3: v;\n\
+                 array [1..2] of var int: e :: output_array([1..2]) = [x,3];\n\
+                 array [1..2] of var int: e2 = e;\n\
+                 constraint int_lin_le(a,[x,y,x],4);\n\
+                 constraint set_in(x,{1,2});\n\
+                 constraint p([],s,1.0);\n\
+                 solve minimize x;\n";
+    let fzn = backward(&forward(input));
+    assert_eq!(
+        fzn,
+        "predicate p(array [int] of int: a, 1..3: b, {1.5,2.5}: c);\n\
+         bool: t = true;\n\
+         float: f = 1.0;\n\
+         set of int: s = 1..3;\n\
+         array [1..3] of int: a = [1, -2, 3];\n\
+         array [1..2] of set of int: ss = [{1,3}, {}];\n\
+         array [1..2] of float: fs = [1.0, 2.5];\n\
+         array [1..0] of int: n = [];\n\
+         var 1..5: x :: output_var;\n\
+         var {1,3}: y = x;\n\
+         var 0.5..1.5: z;\n\
+         var set of 1..3: v;\n\
+         array [1..2] of var int: e :: output_array([1..2]) = [x, 3];\n\
+         array [1..2] of var int: e2 = e;\n\
+         constraint int_lin_le(a, [x, y, x], 4);\n\
+         constraint set_in(x, {1,2});\n\
+         constraint p([], s, 1.0);\n\
+         solve minimize x;\n"
+    );
+    let facts = |fzn: &str| {
+        let lp = forward(fzn);
+        lp.lines()
+            .filter(|l| !l.starts_with('%'))
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    let expanded = |fzn: &str| {
+        let mut lp = vec![];
+        let opts = Options {
+            expand_sets: Some(2),
+            ..opts()
+        };
+        translate(fzn.as_bytes(), &mut lp, opts).unwrap();
+        backward(std::str::from_utf8(&lp).unwrap())
+    };
+    assert_eq!(
+        expanded(
+            "array [1..3] of set of int: ss = [{3,1},1..5,{}];\n\
+             constraint p(ss,4..5);\n\
+             solve satisfy;\n"
+        ),
+        "array [1..3] of set of int: ss = [{1,3}, 1..5, {}];\n\
+         constraint p(ss, {4,5});\n\
+         solve satisfy;\n"
+    );
+    let model = include_str!("../tests/corpus/test.fzn");
+    assert_eq!(facts(&backward(&forward(model))), facts(model));
+    let err = |lp: &str| {
+        reverse(lp.as_bytes(), std::io::sink())
+            .unwrap_err()
+            .to_string()
+    };
+    let constraint = |pos: &str| {
+        format!(
+            "constraint(c1,\"p\").\n\
+             constraint_value(c1,{pos},value,1).\n\
+             solve(satisfy).\n"
+        )
+    };
+    assert_eq!(
+        backward(&constraint("2")),
+        "constraint p([], [], 1);\nsolve satisfy;\n"
+    );
+    assert_eq!(
+        err(&constraint("4000000000")),
+        "Cannot reconstruct c1 from the facts: invalid argument position 4000000000"
+    );
+    assert!(err(&constraint("-1")).contains("invalid argument position -1"));
+}
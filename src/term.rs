@@ -0,0 +1,400 @@
+//! Terms of the facts.
+//!
+//! The terms are formatted directly into the output, so writing a fact allocates no
+//! intermediate strings.
+use crate::{domain_elements, fits_clingo_int, IntRangePolicy, Options};
+use flatzinc::{BoolExpr, FloatExpr, IntExpr, SetExpr, SetLiteral, SetLiteralExpr};
+use std::fmt::{self, Display, Formatter, Write};
+
+/// A string term with `\` and `"` escaped.
+pub(crate) struct Str<'a>(&'a str);
+impl Display for Str<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_char('"')?;
+        for c in self.0.chars() {
+            if c == '\\' || c == '"' {
+                f.write_char('\\')?;
+            }
+            f.write_char(c)?;
+        }
+        f.write_char('"')
+    }
+}
+pub(crate) fn string_term(s: &str) -> Str<'_> {
+    Str(s)
+}
+
+/// The term of a variable or parameter, a constant if [`Options::constants`] is set and the
+/// identifier is a valid symbol.
+#[derive(Clone, Copy)]
+pub(crate) struct Ident<'a> {
+    id: &'a str,
+    constant: bool,
+}
+impl Display for Ident<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.constant {
+            f.write_str(self.id)
+        } else {
+            Str(self.id).fmt(f)
+        }
+    }
+}
+pub(crate) fn ident<'a>(id: &'a str, opts: &Options) -> Ident<'a> {
+    Ident {
+        id,
+        constant: opts.constants && is_symbol(id),
+    }
+}
+/// Whether `id` is a symbolic constant in clingo, that is `_*[a-z]['A-Za-z0-9_]*` but not `not`.
+fn is_symbol(id: &str) -> bool {
+    let name = id.trim_start_matches('_');
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '\'')
+        && id != "not"
+}
+
+/// An integer, quoted if it does not fit into a clingo integer and the policy asks for strings.
+#[derive(Clone, Copy)]
+pub(crate) struct Int {
+    int: i128,
+    quoted: bool,
+}
+impl Int {
+    /// The integer if it fits into a clingo integer, as required in theory atoms.
+    pub(crate) fn fitting(self) -> Option<i128> {
+        fits_clingo_int(self.int).then_some(self.int)
+    }
+}
+impl Display for Int {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.quoted {
+            write!(f, "\"{}\"", self.int)
+        } else {
+            write!(f, "{}", self.int)
+        }
+    }
+}
+pub(crate) fn int_literal(int: i128, opts: &Options) -> Int {
+    Int {
+        int,
+        quoted: opts.int_range == IntRangePolicy::String && !fits_clingo_int(int),
+    }
+}
+/// The bound of a domain, clamped to clingo's integer range if requested.
+///
+/// Domains that do not meet the range are rejected before, so clamping keeps a subset of them.
+pub(crate) fn int_bound(int: i128, opts: &Options) -> Int {
+    if opts.int_range == IntRangePolicy::Clamp {
+        Int {
+            int: int.clamp(i32::MIN.into(), i32::MAX.into()),
+            quoted: false,
+        }
+    } else {
+        int_literal(int, opts)
+    }
+}
+pub(crate) fn bool_literal(b: bool) -> &'static str {
+    if b {
+        "true"
+    } else {
+        "false"
+    }
+}
+
+/// A value `value,V` or an identifier `var,X`.
+#[derive(Clone, Copy)]
+pub(crate) enum Scalar<'a> {
+    Bool(bool),
+    Int(Int),
+    Float(f64),
+    Var(Ident<'a>),
+}
+impl Display for Scalar<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Scalar::Bool(b) => write!(f, "value,{}", bool_literal(*b)),
+            Scalar::Int(int) => write!(f, "value,{int}"),
+            Scalar::Float(float) => write!(f, "value,\"{float}\""),
+            Scalar::Var(id) => write!(f, "var,{id}"),
+        }
+    }
+}
+pub(crate) fn bool_expr<'a>(e: &'a BoolExpr, opts: &Options) -> Scalar<'a> {
+    match e {
+        BoolExpr::Bool(b) => Scalar::Bool(*b),
+        BoolExpr::VarParIdentifier(id) => Scalar::Var(ident(id, opts)),
+    }
+}
+pub(crate) fn int_expr<'a>(e: &'a IntExpr, opts: &Options) -> Scalar<'a> {
+    match e {
+        IntExpr::Int(int) => Scalar::Int(int_literal(*int, opts)),
+        IntExpr::VarParIdentifier(id) => Scalar::Var(ident(id, opts)),
+    }
+}
+pub(crate) fn float_expr<'a>(e: &'a FloatExpr, opts: &Options) -> Scalar<'a> {
+    match e {
+        FloatExpr::Float(float) => Scalar::Float(*float),
+        FloatExpr::VarParIdentifier(id) => Scalar::Var(ident(id, opts)),
+    }
+}
+
+/// A set, or one element of it, written as one fact each.
+pub(crate) enum SetTerm<'a> {
+    Empty,
+    Range(Scalar<'a>, Scalar<'a>),
+    Bounds(Scalar<'a>, Scalar<'a>),
+    Element(Scalar<'a>),
+    Var(Ident<'a>),
+    /// An element of an expanded set
+    Elem(Int),
+    /// The number of elements of a constant set, `None` for the 2^128 elements of all integers
+    Size(Option<u128>),
+}
+impl Display for SetTerm<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SetTerm::Empty => f.write_str("empty_set"),
+            SetTerm::Range(lb, ub) => write!(f, "range,({lb},{ub})"),
+            SetTerm::Bounds(lb, ub) => write!(f, "bounds,({lb},{ub})"),
+            SetTerm::Element(e) => write!(f, "set,({e})"),
+            SetTerm::Var(id) => write!(f, "var,{id}"),
+            SetTerm::Elem(int) => write!(f, "elem,{int}"),
+            SetTerm::Size(Some(size)) => write!(f, "set_size,{size}"),
+            SetTerm::Size(None) => f.write_str("set_size,340282366920938463463374607431768211456"),
+        }
+    }
+}
+pub(crate) fn dec_set_expr<'a>(
+    e: &'a SetExpr,
+    opts: &'a Options,
+) -> impl Iterator<Item = SetTerm<'a>> + 'a {
+    let (var, literal) = match e {
+        SetExpr::Set(sl) => (None, Some(sl)),
+        SetExpr::VarParIdentifier(id) => (Some(SetTerm::Var(ident(id, opts))), None),
+    };
+    var.into_iter().chain(
+        literal
+            .into_iter()
+            .flat_map(move |sl| dec_set_literal_expr(sl, opts)),
+    )
+}
+pub(crate) fn dec_set_literal_expr<'a>(
+    l: &'a SetLiteralExpr,
+    opts: &'a Options,
+) -> impl Iterator<Item = SetTerm<'a>> + 'a {
+    let int_set = opts.expand_sets.and_then(|_| match l {
+        SetLiteralExpr::IntInRange(IntExpr::Int(lb), IntExpr::Int(ub)) => {
+            Some(IntSet::Range(*lb, *ub))
+        }
+        SetLiteralExpr::SetInts(v) => v
+            .iter()
+            .map(|e| match e {
+                IntExpr::Int(i) => Some(*i),
+                IntExpr::VarParIdentifier(_) => None,
+            })
+            .collect::<Option<_>>()
+            .map(IntSet::elements),
+        _ => None,
+    });
+    expand(int_set, opts, move || set_literal_expr_terms(l, opts))
+}
+fn set_literal_expr_terms<'a>(
+    l: &'a SetLiteralExpr,
+    opts: &'a Options,
+) -> impl Iterator<Item = SetTerm<'a>> + 'a {
+    let (term, ints, floats): (_, &[IntExpr], &[FloatExpr]) = match l {
+        SetLiteralExpr::BoundedFloat(lb, ub) => (
+            Some(SetTerm::Bounds(float_expr(lb, opts), float_expr(ub, opts))),
+            &[],
+            &[],
+        ),
+        SetLiteralExpr::IntInRange(lb, ub) => (
+            Some(SetTerm::Range(int_expr(lb, opts), int_expr(ub, opts))),
+            &[],
+            &[],
+        ),
+        SetLiteralExpr::SetFloats(v) => (v.is_empty().then_some(SetTerm::Empty), &[], v),
+        SetLiteralExpr::SetInts(v) => (v.is_empty().then_some(SetTerm::Empty), v, &[]),
+    };
+    term.into_iter()
+        .chain(ints.iter().map(|i| SetTerm::Element(int_expr(i, opts))))
+        .chain(floats.iter().map(|f| SetTerm::Element(float_expr(f, opts))))
+}
+pub(crate) fn dec_set_literal<'a>(
+    l: &'a SetLiteral,
+    opts: &'a Options,
+) -> impl Iterator<Item = SetTerm<'a>> + 'a {
+    let int_set = opts.expand_sets.and_then(|_| match l {
+        SetLiteral::IntRange(lb, ub) => Some(IntSet::Range(*lb, *ub)),
+        SetLiteral::SetInts(v) => Some(IntSet::elements(v.clone())),
+        _ => None,
+    });
+    expand(int_set, opts, move || set_literal_terms(l, opts))
+}
+fn set_literal_terms<'a>(
+    l: &'a SetLiteral,
+    opts: &'a Options,
+) -> impl Iterator<Item = SetTerm<'a>> + 'a {
+    let (term, ints, floats): (_, &[i128], &[f64]) = match l {
+        SetLiteral::BoundedFloat(lb, ub) => (
+            Some(SetTerm::Bounds(Scalar::Float(*lb), Scalar::Float(*ub))),
+            &[],
+            &[],
+        ),
+        SetLiteral::IntRange(lb, ub) => (
+            Some(SetTerm::Range(
+                Scalar::Int(int_literal(*lb, opts)),
+                Scalar::Int(int_literal(*ub, opts)),
+            )),
+            &[],
+            &[],
+        ),
+        SetLiteral::SetFloats(v) => (v.is_empty().then_some(SetTerm::Empty), &[], v),
+        SetLiteral::SetInts(v) => (v.is_empty().then_some(SetTerm::Empty), v, &[]),
+    };
+    term.into_iter()
+        .chain(
+            ints.iter()
+                .map(|i| SetTerm::Element(Scalar::Int(int_literal(*i, opts)))),
+        )
+        .chain(floats.iter().map(|f| SetTerm::Element(Scalar::Float(*f))))
+}
+/// A constant set of integers.
+enum IntSet {
+    Range(i128, i128),
+    /// Sorted distinct elements
+    Elements(Vec<i128>),
+}
+impl IntSet {
+    fn elements(mut v: Vec<i128>) -> IntSet {
+        v.sort_unstable();
+        v.dedup();
+        IntSet::Elements(v)
+    }
+    /// The number of elements, `None` if it does not fit into `u128`.
+    fn size(&self) -> Option<u128> {
+        match self {
+            IntSet::Range(lb, ub) if lb <= ub => ub.abs_diff(*lb).checked_add(1),
+            IntSet::Range(..) => Some(0),
+            IntSet::Elements(v) => Some(v.len() as u128),
+        }
+    }
+    fn into_iter(self) -> impl Iterator<Item = i128> {
+        let (range, v) = match self {
+            IntSet::Range(lb, ub) => (Some(lb..=ub), vec![]),
+            IntSet::Elements(v) => (None, v),
+        };
+        range.into_iter().flatten().chain(v)
+    }
+}
+/// Writes a constant set of integers as its elements and size if [`Options::expand_sets`] is
+/// set, sets with more elements than the limit keep the terms of `terms` besides their size.
+fn expand<'a, I: Iterator<Item = SetTerm<'a>> + 'a>(
+    set: Option<IntSet>,
+    opts: &'a Options,
+    terms: impl FnOnce() -> I,
+) -> impl Iterator<Item = SetTerm<'a>> + 'a {
+    let (set, size) = match (set, opts.expand_sets) {
+        (Some(set), Some(max)) => {
+            let size = set.size();
+            (
+                size.is_some_and(|size| size <= max as u128).then_some(set),
+                Some(SetTerm::Size(size)),
+            )
+        }
+        _ => (None, None),
+    };
+    let terms = set.is_none().then(terms);
+    set.into_iter()
+        .flat_map(move |set| set.into_iter().map(|i| SetTerm::Elem(int_literal(i, opts))))
+        .chain(terms.into_iter().flatten())
+        .chain(size)
+}
+/// The elements of a set type like `int,set,(value,V)`, written as one fact each.
+pub(crate) fn set_type<'a>(
+    kind: &'static str,
+    set: &'a [i128],
+    opts: &'a Options,
+) -> impl Iterator<Item = impl Display + 'a> + 'a {
+    domain_elements(set, opts).map(move |i| {
+        let element = SetTerm::Element(Scalar::Int(int_literal(*i, opts)));
+        Typed(kind, element)
+    })
+}
+/// A term prefixed by its type.
+struct Typed<T>(&'static str, T);
+impl<T: Display> Display for Typed<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.0, self.1)
+    }
+}
+
+#[test]
+fn test_terms() {
+    let mut opts = Options::default();
+    assert_eq!(int_literal(3_000_000_000, &opts).to_string(), "3000000000");
+    opts.int_range = IntRangePolicy::String;
+    assert_eq!(
+        int_literal(3_000_000_000, &opts).to_string(),
+        "\"3000000000\""
+    );
+    opts.int_range = IntRangePolicy::Clamp;
+    assert_eq!(int_bound(-3_000_000_000, &opts).to_string(), "-2147483648");
+    let set = SetLiteralExpr::SetInts(vec![IntExpr::Int(1), IntExpr::VarParIdentifier("x".into())]);
+    let terms: Vec<_> = dec_set_literal_expr(&set, &opts)
+        .map(|t| t.to_string())
+        .collect();
+    assert_eq!(terms, ["set,(value,1)", "set,(var,\"x\")"]);
+    let terms: Vec<_> = dec_set_literal(&SetLiteral::SetFloats(vec![]), &opts)
+        .map(|t| t.to_string())
+        .collect();
+    assert_eq!(terms, ["empty_set"]);
+    let types: Vec<_> = set_type("int", &[1, 1 << 40], &opts)
+        .map(|t| t.to_string())
+        .collect();
+    assert_eq!(types, ["int,set,(value,1)"]);
+}
+#[test]
+fn test_expand_sets() {
+    let terms = |set: &SetLiteral, opts: &Options| {
+        dec_set_literal(set, opts)
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>()
+    };
+    let range = SetLiteral::IntRange(3, 5);
+    let mut opts = Options::default();
+    assert_eq!(terms(&range, &opts), ["range,(value,3,value,5)"]);
+    opts.expand_sets = Some(3);
+    assert_eq!(
+        terms(&range, &opts),
+        ["elem,3", "elem,4", "elem,5", "set_size,3"]
+    );
+    assert_eq!(terms(&SetLiteral::IntRange(5, 3), &opts), ["set_size,0"]);
+    let set = SetLiteral::SetInts(vec![4, 1, 4]);
+    assert_eq!(terms(&set, &opts), ["elem,1", "elem,4", "set_size,2"]);
+    let floats = SetLiteral::SetFloats(vec![1.5]);
+    assert_eq!(terms(&floats, &opts), ["set,(value,\"1.5\")"]);
+    opts.expand_sets = Some(2);
+    assert_eq!(
+        terms(&range, &opts),
+        ["range,(value,3,value,5)", "set_size,3"]
+    );
+    let all = SetLiteral::IntRange(i128::MIN, i128::MAX);
+    assert_eq!(
+        terms(&all, &opts),
+        [
+            "range,(value,-170141183460469231731687303715884105728,\
+             value,170141183460469231731687303715884105727)",
+            "set_size,340282366920938463463374607431768211456"
+        ]
+    );
+    let with_var =
+        SetLiteralExpr::SetInts(vec![IntExpr::Int(1), IntExpr::VarParIdentifier("x".into())]);
+    let terms: Vec<_> = dec_set_literal_expr(&with_var, &opts)
+        .map(|t| t.to_string())
+        .collect();
+    assert_eq!(terms, ["set,(value,1)", "set,(var,\"x\")"]);
+}
@@ -0,0 +1,83 @@
+//! Tests of the binary, for what only shows in its exit code and on stderr.
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+/// Runs the binary with `args` on `input` as stdin.
+fn run(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fzn2lp"))
+        .args(args)
+        .env_remove("RUST_LOG")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn int_range_warning() {
+    let output = run(&[], "var -3000000000..5 : a;\nsolve satisfy;\n");
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Integers outside of clingo's 32-bit range in: a"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn order_warning() {
+    let output = run(&[], "var 1..3 : x;\nint : k = 1;\nsolve satisfy;\n");
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Statements in wrong order: parameter in line 2."),
+        "{stderr}"
+    );
+    let output = run(&["--strict"], "var 1..3 : x;\nint : k = 1;\n");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn file_named_like_subcommand() {
+    let dir = std::env::temp_dir().join(format!("fzn2lp_cli_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("fmt"), "var 1..3 : x;\nsolve satisfy;\n").unwrap();
+    let run_in = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_fzn2lp"))
+            .args(args)
+            .current_dir(&dir)
+            .stdin(Stdio::null())
+            .output()
+            .unwrap()
+    };
+    let output = run_in(&["fmt"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("variable_type(\"x\",int,range,(value,1,value,3))."),
+        "{stdout}"
+    );
+    let output = run_in(&["fmt", "fmt"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "var 1..3: x;\nsolve satisfy;\n"
+    );
+    let output = run(&["fmt"], "solve satisfy;\n");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "solve satisfy;\n"
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,180 @@
+//! Property tests translating random FlatZinc statements.
+//!
+//! The translation of a statement may fail, but it must not panic and every line it writes has
+//! to be a comment or a single fact.
+use fzn2lp::{facts::parse_facts, write_fz_stmt};
+use proptest::{prelude::*, sample::select};
+
+const KEYWORDS: &[&str] = &[
+    "annotation",
+    "any",
+    "array",
+    "bool",
+    "constraint",
+    "false",
+    "float",
+    "int",
+    "list",
+    "maximize",
+    "minimize",
+    "of",
+    "predicate",
+    "satisfy",
+    "set",
+    "solve",
+    "true",
+    "var",
+];
+
+fn ident() -> impl Strategy<Value = String> {
+    "_{0,2}[A-Za-z][A-Za-z0-9_]{0,6}".prop_filter("keyword", |id| !KEYWORDS.contains(&id.as_str()))
+}
+fn int() -> impl Strategy<Value = i128> {
+    prop_oneof![-20i128..20, any::<i32>().prop_map(i128::from)]
+}
+fn float() -> impl Strategy<Value = String> {
+    (-1e6f64..1e6).prop_map(|f| format!("{f:?}"))
+}
+fn list(items: Vec<String>, open: &str, close: &str) -> String {
+    format!("{open}{}{close}", items.join(","))
+}
+fn int_set() -> impl Strategy<Value = String> {
+    prop_oneof![
+        (int(), int()).prop_map(|(lb, ub)| format!("{lb}..{ub}")),
+        prop::collection::vec(int(), 0..4).prop_map(|v| list(
+            v.iter().map(i128::to_string).collect(),
+            "{",
+            "}"
+        )),
+    ]
+}
+fn float_set() -> impl Strategy<Value = String> {
+    prop_oneof![
+        (float(), float()).prop_map(|(lb, ub)| format!("{lb}..{ub}")),
+        prop::collection::vec(float(), 1..4).prop_map(|v| list(v, "{", "}")),
+    ]
+}
+fn int_or_ident() -> impl Strategy<Value = String> {
+    prop_oneof![int().prop_map(|i| i.to_string()), ident()]
+}
+fn expr() -> impl Strategy<Value = String> {
+    prop_oneof![
+        ident(),
+        any::<bool>().prop_map(|b| b.to_string()),
+        int().prop_map(|i| i.to_string()),
+        float(),
+        int_set(),
+        float_set(),
+        prop::collection::vec(int_or_ident(), 0..4).prop_map(|v| list(v, "[", "]")),
+        prop::collection::vec(float(), 1..4).prop_map(|v| list(v, "[", "]")),
+        prop::collection::vec(int_set(), 1..3).prop_map(|v| list(v, "[", "]")),
+    ]
+}
+fn annotation() -> impl Strategy<Value = String> {
+    let index_set = prop_oneof![int_set(), ident()];
+    prop_oneof![
+        Just("output_var".to_string()),
+        prop::collection::vec(index_set, 1..3)
+            .prop_map(|v| format!("output_array({})", list(v, "[", "]"))),
+        ident(),
+        (ident(), prop::collection::vec(expr(), 1..3))
+            .prop_map(|(id, v)| format!("{id}{}", list(v, "(", ")"))),
+        (ident(), "[a-z .|0-9]{0,10}").prop_map(|(id, s)| format!("{id}(\"{s}\")")),
+    ]
+}
+fn annotations() -> impl Strategy<Value = String> {
+    prop::collection::vec(annotation(), 0..3)
+        .prop_map(|v| v.iter().map(|a| format!(" :: {a}")).collect())
+}
+fn var_type() -> impl Strategy<Value = String> {
+    prop_oneof![
+        select(vec!["bool", "int", "float", "set of int"]).prop_map(str::to_string),
+        int_set(),
+        (float(), float()).prop_map(|(lb, ub)| format!("{lb}..{ub}")),
+        int_set().prop_map(|s| format!("set of {s}")),
+    ]
+}
+fn pred_par_type() -> impl Strategy<Value = String> {
+    prop_oneof![
+        var_type(),
+        var_type().prop_map(|t| format!("var {t}")),
+        float_set(),
+        (
+            prop_oneof![
+                Just("int".to_string()),
+                (1u8..5).prop_map(|n| format!("1..{n}"))
+            ],
+            var_type()
+        )
+            .prop_map(|(ix, t)| format!("array [{ix}] of var {t}")),
+    ]
+}
+fn stmt() -> impl Strategy<Value = String> {
+    prop_oneof![
+        (
+            ident(),
+            prop::collection::vec((pred_par_type(), ident()), 1..4)
+        )
+            .prop_map(|(id, params)| {
+                let params = params.iter().map(|(t, p)| format!("{t}: {p}")).collect();
+                format!("predicate {id}{};", list(params, "(", ")"))
+            }),
+        (ident(), int()).prop_map(|(id, i)| format!("int: {id} = {i};")),
+        (ident(), any::<bool>()).prop_map(|(id, b)| format!("bool: {id} = {b};")),
+        (ident(), float()).prop_map(|(id, f)| format!("float: {id} = {f};")),
+        (ident(), int_set()).prop_map(|(id, s)| format!("set of int: {id} = {s};")),
+        (ident(), prop::collection::vec(int(), 1..5)).prop_map(|(id, v)| format!(
+            "array [1..{}] of int: {id} = {};",
+            v.len(),
+            list(v.iter().map(i128::to_string).collect(), "[", "]")
+        )),
+        (ident(), prop::collection::vec(int_set(), 1..4)).prop_map(|(id, v)| format!(
+            "array [1..{}] of set of int: {id} = {};",
+            v.len(),
+            list(v, "[", "]")
+        )),
+        (var_type(), ident(), annotations())
+            .prop_map(|(t, id, annos)| format!("var {t}: {id}{annos};")),
+        (int_set(), ident(), annotations(), int_or_ident())
+            .prop_map(|(t, id, annos, e)| format!("var {t}: {id}{annos} = {e};")),
+        (
+            var_type(),
+            ident(),
+            annotations(),
+            prop::collection::vec(int_or_ident(), 1..5)
+        )
+            .prop_map(|(t, id, annos, v)| format!(
+                "array [1..{}] of var {t}: {id}{annos} = {};",
+                v.len(),
+                list(v, "[", "]")
+            )),
+        (ident(), prop::collection::vec(expr(), 1..4), annotations())
+            .prop_map(|(id, v, annos)| format!("constraint {id}{}{annos};", list(v, "(", ")"))),
+        Just("solve satisfy;".to_string()),
+        (select(vec!["minimize", "maximize"]), ident())
+            .prop_map(|(ot, id)| format!("solve {ot} {id};")),
+    ]
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2000))]
+    #[test]
+    fn translation_writes_facts(stmts in prop::collection::vec(stmt(), 1..8)) {
+        let mut constraint_counter = 0;
+        let mut level = 0;
+        for stmt in &stmts {
+            let mut out = vec![];
+            if write_fz_stmt(&mut out, stmt, &mut constraint_counter, &mut level).is_err() {
+                continue;
+            }
+            let out = String::from_utf8(out).unwrap();
+            for line in out.lines().filter(|l| !l.starts_with('%')) {
+                let facts = parse_facts(line);
+                prop_assert!(
+                    matches!(facts.as_deref(), Ok([_])),
+                    "{stmt} gives invalid fact {line}: {facts:?}"
+                );
+            }
+        }
+    }
+}
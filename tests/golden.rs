@@ -0,0 +1,105 @@
+//! Golden file tests on the models in `tests/corpus`.
+//!
+//! Each `.fzn` file is translated with the default options and the facts are compared with the
+//! `.lp` file of the same name, or the error with the `.err` file if the translation fails.
+//! Run with `FZN2LP_BLESS=1` to write the current outputs as new golden files.
+use fzn2lp::{format::format, reverse::reverse, translate, Options};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+fn corpus() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "fzn"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "empty corpus");
+    paths
+}
+
+/// The facts of a model, or the error message if the translation fails.
+fn facts(fzn: &[u8]) -> Result<String, String> {
+    let mut lp = vec![];
+    match translate(fzn, &mut lp, Options::default()).and_then(|t| t.finish()) {
+        Ok(()) => Ok(String::from_utf8(lp).unwrap()),
+        Err(err) => Err(format!("{err}\n")),
+    }
+}
+
+/// The facts without the comments copied from the model.
+fn without_comments(lp: &str) -> Vec<&str> {
+    lp.lines().filter(|l| !l.starts_with('%')).collect()
+}
+
+#[test]
+fn golden() {
+    let bless = env::var_os("FZN2LP_BLESS").is_some();
+    let mut failures = vec![];
+    for path in corpus() {
+        let (actual, golden, stale) = match facts(&fs::read(&path).unwrap()) {
+            Ok(lp) => (lp, path.with_extension("lp"), path.with_extension("err")),
+            Err(err) => (err, path.with_extension("err"), path.with_extension("lp")),
+        };
+        if bless {
+            fs::write(&golden, &actual).unwrap();
+            if stale.exists() {
+                fs::remove_file(stale).unwrap();
+            }
+            continue;
+        }
+        let Ok(expected) = fs::read_to_string(&golden) else {
+            failures.push(format!("{}: missing", golden.display()));
+            continue;
+        };
+        let diff = expected
+            .lines()
+            .zip(actual.lines())
+            .position(|(e, a)| e != a)
+            .or((expected.lines().count() != actual.lines().count())
+                .then(|| expected.lines().count().min(actual.lines().count())));
+        if let Some(i) = diff {
+            failures.push(format!(
+                "{}:{}: expected {:?}, found {:?}",
+                golden.display(),
+                i + 1,
+                expected.lines().nth(i).unwrap_or_default(),
+                actual.lines().nth(i).unwrap_or_default()
+            ));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{}\nrun with FZN2LP_BLESS=1 to update the golden files",
+        failures.join("\n")
+    );
+}
+
+#[test]
+fn round_trip() {
+    for path in corpus() {
+        let fzn = fs::read(&path).unwrap();
+        let Ok(lp) = facts(&fzn) else {
+            continue;
+        };
+        let mut formatted = vec![];
+        format(fzn.as_slice(), &mut formatted).unwrap();
+        assert_eq!(
+            without_comments(&facts(&formatted).unwrap()),
+            without_comments(&lp),
+            "fmt of {}",
+            path.display()
+        );
+        let mut reversed = vec![];
+        reverse(lp.as_bytes(), &mut reversed).unwrap();
+        assert_eq!(
+            without_comments(&facts(&reversed).unwrap()),
+            without_comments(&lp),
+            "reverse of {}",
+            path.display()
+        );
+    }
+}